@@ -20,7 +20,7 @@ async def run_init(pool: asyncpg.Pool):
 async def get_monitor_statuses(pool: asyncpg.Pool, project_id: str | None) -> list[dict]:
     return await pool.fetch(
         """SELECT project_id, site_key, url, status_code, response_ms,
-                  is_up, error_type, error_message, last_checked_at, last_up_at
+                  is_up, is_degraded, paused, error_type, error_message, last_checked_at, last_up_at
            FROM monitor_status
            WHERE ($1::text IS NULL OR project_id = $1)
            ORDER BY project_id, site_key""",
@@ -33,7 +33,7 @@ class HourlyRow:
     project_id: str
     site_key: str
     hour: datetime
-    all_up: bool
+    all_up: bool | None
 
 
 def build_hourly_summary(rows: list[HourlyRow]) -> HourlySummary:
@@ -51,7 +51,8 @@ def build_hourly_summary(rows: list[HourlyRow]) -> HourlySummary:
             day_entry = DayChecks(day=date, checks=[None] * 24)
             entry.days.append(day_entry)
 
-        day_entry.checks[hour_idx] = 1 if row.all_up else 0
+        if row.all_up is not None:
+            day_entry.checks[hour_idx] = 1 if row.all_up else 0
 
     return result
 
@@ -60,7 +61,7 @@ async def get_hourly_summary(pool: asyncpg.Pool, project_id: str | None, days: i
     rows = await pool.fetch(
         """SELECT project_id, site_key,
                   time_bucket('1 hour', checked_at) AS hour,
-                  bool_and(is_up) AS all_up
+                  bool_and(is_up) FILTER (WHERE NOT in_maintenance) AS all_up
            FROM monitor_checks
            WHERE checked_at > NOW() - make_interval(days => $1)
              AND ($2::text IS NULL OR project_id = $2)
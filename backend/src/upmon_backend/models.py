@@ -8,11 +8,13 @@ class MonitorStatus(BaseModel):
     site_key: str
     url: str
     status_code: int | None
-    response_ms: int
-    is_up: bool
+    response_ms: int | None
+    is_up: bool | None
+    is_degraded: bool
+    paused: bool
     error_type: str | None
     error_message: str | None
-    last_checked_at: datetime
+    last_checked_at: datetime | None
     last_up_at: datetime | None
 
 
@@ -28,6 +28,8 @@ def _status_row(project_id, site_key):
         "status_code": 200,
         "response_ms": 10,
         "is_up": True,
+        "is_degraded": False,
+        "paused": False,
         "error_type": None,
         "error_message": None,
         "last_checked_at": "2026-07-16T12:00:00+00:00",
@@ -3,7 +3,7 @@ from datetime import date, datetime, timezone
 from upmon_backend.db import HourlyRow, build_hourly_summary
 
 
-def _row(project: str, site: str, hour_str: str, all_up: bool) -> HourlyRow:
+def _row(project: str, site: str, hour_str: str, all_up: bool | None) -> HourlyRow:
     return HourlyRow(
         project_id=project,
         site_key=site,
@@ -56,3 +56,14 @@ def test_hours_spanning_two_days():
     assert days[0].checks[23] == 1
     assert days[1].day == date(2025, 1, 16)
     assert days[1].checks[0] == 0
+
+
+def test_maintenance_only_hour_has_no_data():
+    rows = [
+        _row("p1", "s1", "2025-01-15T02:00:00", None),
+        _row("p1", "s1", "2025-01-15T03:00:00", True),
+    ]
+    result = build_hourly_summary(rows)
+    days = result["p1"]["s1"].days
+    assert days[0].checks[2] is None
+    assert days[0].checks[3] == 1
@@ -0,0 +1,11 @@
+CREATE TABLE retention_runs (
+    id             BIGSERIAL    PRIMARY KEY,
+    executed_at    TIMESTAMPTZ  NOT NULL,
+    retention_days INT          NOT NULL,
+    deleted_count  BIGINT,
+    duration_ms    INT          NOT NULL,
+    error_message  TEXT
+);
+
+CREATE INDEX idx_retention_runs_executed_at
+    ON retention_runs (executed_at DESC);
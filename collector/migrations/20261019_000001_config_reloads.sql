@@ -0,0 +1,12 @@
+CREATE TABLE config_reloads (
+    id            BIGSERIAL    PRIMARY KEY,
+    reloaded_at   TIMESTAMPTZ  NOT NULL,
+    success       BOOLEAN      NOT NULL,
+    monitor_count INT,
+    error_message TEXT,
+    error_line    INT,
+    error_column  INT
+);
+
+CREATE INDEX idx_config_reloads_reloaded_at
+    ON config_reloads (reloaded_at DESC);
@@ -0,0 +1,2 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN is_retry BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,17 @@
+CREATE TABLE incidents (
+    id                  BIGSERIAL    PRIMARY KEY,
+    project_id          TEXT         NOT NULL,
+    site_key            TEXT         NOT NULL,
+    started_at          TIMESTAMPTZ  NOT NULL,
+    ended_at            TIMESTAMPTZ,
+    duration_ms         BIGINT,
+    failed_checks       INT          NOT NULL,
+    first_error_type    TEXT,
+    first_error_message TEXT
+);
+
+CREATE INDEX idx_incidents_monitor_time
+    ON incidents (project_id, site_key, started_at DESC);
+
+CREATE UNIQUE INDEX idx_incidents_open
+    ON incidents (project_id, site_key) WHERE ended_at IS NULL;
@@ -0,0 +1,16 @@
+CREATE TABLE alert_deliveries (
+    id            BIGSERIAL    PRIMARY KEY,
+    sent_at       TIMESTAMPTZ  NOT NULL,
+    project_id    TEXT         NOT NULL,
+    site_key      TEXT         NOT NULL,
+    event         TEXT         NOT NULL,
+    notifier      TEXT         NOT NULL,
+    target        TEXT         NOT NULL,
+    success       BOOLEAN      NOT NULL,
+    attempts      INT          NOT NULL,
+    status_code   SMALLINT,
+    error_message TEXT
+);
+
+CREATE INDEX idx_alert_deliveries_monitor_time
+    ON alert_deliveries (project_id, site_key, sent_at DESC);
@@ -0,0 +1,5 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN in_maintenance BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE monitor_status
+    ADD COLUMN in_maintenance BOOLEAN NOT NULL DEFAULT FALSE;
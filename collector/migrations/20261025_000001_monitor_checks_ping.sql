@@ -0,0 +1,6 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN packet_loss_pct REAL,
+    ADD COLUMN rtt_min_ms REAL,
+    ADD COLUMN rtt_avg_ms REAL,
+    ADD COLUMN rtt_max_ms REAL,
+    ADD COLUMN jitter_ms REAL;
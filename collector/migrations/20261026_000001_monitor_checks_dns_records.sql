@@ -0,0 +1,2 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN dns_records TEXT[];
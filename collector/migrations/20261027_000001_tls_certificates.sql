@@ -0,0 +1,14 @@
+CREATE TABLE tls_certificates (
+    project_id     TEXT         NOT NULL,
+    site_key       TEXT         NOT NULL,
+    subject        TEXT         NOT NULL,
+    issuer         TEXT         NOT NULL,
+    not_before     TIMESTAMPTZ  NOT NULL,
+    not_after      TIMESTAMPTZ  NOT NULL,
+    sans           TEXT[]       NOT NULL,
+    chain_valid    BOOLEAN,
+    hostname_match BOOLEAN      NOT NULL,
+    expiry_warning BOOLEAN      NOT NULL,
+    checked_at     TIMESTAMPTZ  NOT NULL,
+    PRIMARY KEY (project_id, site_key)
+);
@@ -0,0 +1,6 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN dns_ms INTEGER,
+    ADD COLUMN connect_ms INTEGER,
+    ADD COLUMN tls_ms INTEGER,
+    ADD COLUMN ttfb_ms INTEGER,
+    ADD COLUMN download_ms INTEGER;
@@ -0,0 +1,15 @@
+ALTER TABLE monitor_checks
+    ADD COLUMN is_degraded BOOLEAN NOT NULL DEFAULT FALSE;
+
+ALTER TABLE monitor_status
+    ADD COLUMN is_degraded      BOOLEAN NOT NULL DEFAULT FALSE,
+    ADD COLUMN consecutive_slow INT     NOT NULL DEFAULT 0,
+    ADD COLUMN consecutive_fast INT     NOT NULL DEFAULT 0;
+
+-- 'down' for outages, 'degraded' for slowness; a monitor has at most one open incident of each kind.
+ALTER TABLE incidents
+    ADD COLUMN kind TEXT NOT NULL DEFAULT 'down';
+
+DROP INDEX idx_incidents_open;
+CREATE UNIQUE INDEX idx_incidents_open
+    ON incidents (project_id, site_key, kind) WHERE ended_at IS NULL;
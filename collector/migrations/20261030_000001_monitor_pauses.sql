@@ -0,0 +1,11 @@
+ALTER TABLE monitor_status
+    ADD COLUMN paused    BOOLEAN     NOT NULL DEFAULT FALSE,
+    ADD COLUMN paused_at TIMESTAMPTZ;
+
+-- Pauses set through the admin API; pauses from config.json live only there.
+CREATE TABLE monitor_pauses (
+    project_id TEXT        NOT NULL,
+    site_key   TEXT        NOT NULL,
+    paused_at  TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (project_id, site_key)
+);
@@ -0,0 +1,5 @@
+-- A monitor paused before its first check has a status row but no check results yet.
+ALTER TABLE monitor_status
+    ALTER COLUMN response_ms DROP NOT NULL,
+    ALTER COLUMN is_up DROP NOT NULL,
+    ALTER COLUMN last_checked_at DROP NOT NULL;
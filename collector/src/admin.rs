@@ -0,0 +1,512 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::extract::{Path, Query, Request, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use chrono::Utc;
+use serde::Deserialize;
+use serde_json::{Value, json};
+use tokio::net::TcpListener;
+use tracing::{error, info};
+
+use crate::config::{CheckSpec, RequestBody, ResolvedMonitor};
+use crate::models::CheckResult;
+use crate::scheduler::{MonitorManager, MonitorRuntime};
+use crate::secrets;
+use crate::trigger::Scope;
+
+#[derive(Clone)]
+struct AdminState {
+    manager: Arc<MonitorManager>,
+    token: Arc<str>,
+}
+
+/// Serves the admin API until the listener fails. Every route requires `Authorization: Bearer <token>`.
+pub async fn serve(listener: TcpListener, token: String, manager: Arc<MonitorManager>) {
+    if let Err(e) = axum::serve(listener, router(manager, token)).await {
+        error!(error = %e, "admin API stopped");
+    }
+}
+
+fn router(manager: Arc<MonitorManager>, token: String) -> Router {
+    let state = AdminState { manager, token: token.into() };
+    Router::new()
+        .route("/status", get(status))
+        .route("/metrics", get(metrics))
+        .route("/probe", get(probe))
+        .route("/monitors", get(list_monitors))
+        .route("/monitors/{project_id}/{site_key}", get(get_monitor))
+        .route("/check", post(check_all))
+        .route("/projects/{project_id}/check", post(check_project))
+        .route("/monitors/{project_id}/{site_key}/check", post(check_monitor))
+        .route("/monitors/{project_id}/{site_key}/pause", post(pause_monitor))
+        .route("/monitors/{project_id}/{site_key}/resume", post(resume_monitor))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_token))
+        .with_state(state)
+}
+
+async fn require_token(State(state): State<AdminState>, request: Request, next: Next) -> Response {
+    let authorized = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .is_some_and(|token| constant_time_eq(token.as_bytes(), state.token.as_bytes()));
+    if !authorized {
+        return (
+            StatusCode::UNAUTHORIZED,
+            [(header::WWW_AUTHENTICATE, "Bearer")],
+            Json(json!({ "error": "missing or invalid bearer token" })),
+        )
+            .into_response();
+    }
+    next.run(request).await
+}
+
+/// Compares without returning early, so response time doesn't reveal how much of the token matched.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+async fn status(State(state): State<AdminState>) -> Json<Value> {
+    let started_at = state.manager.started_at();
+    Json(json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "started_at": started_at,
+        "uptime_sec": (Utc::now() - started_at).num_seconds().max(0),
+        "monitor_count": state.manager.snapshot().len(),
+    }))
+}
+
+const EXPOSITION_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Share of Prometheus' scrape timeout left for sending the probe's answer, as in blackbox_exporter.
+const SCRAPE_TIMEOUT_OFFSET: Duration = Duration::from_millis(500);
+
+async fn metrics(State(state): State<AdminState>) -> Response {
+    ([(header::CONTENT_TYPE, EXPOSITION_CONTENT_TYPE)], state.manager.metrics().render()).into_response()
+}
+
+#[derive(Deserialize)]
+struct ProbeParams {
+    #[serde(default)]
+    target: String,
+    #[serde(default = "default_probe_module")]
+    module: String,
+}
+
+fn default_probe_module() -> String {
+    "http_2xx".into()
+}
+
+/// blackbox_exporter-style `/probe?target=...&module=...`, bounded by the scrape timeout Prometheus sends.
+async fn probe(State(state): State<AdminState>, headers: HeaderMap, Query(params): Query<ProbeParams>) -> Response {
+    let timeout = headers
+        .get("X-Prometheus-Scrape-Timeout-Seconds")
+        .and_then(|value| value.to_str().ok()?.parse::<f64>().ok())
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .and_then(|scrape_timeout| scrape_timeout.checked_sub(SCRAPE_TIMEOUT_OFFSET))
+        .filter(|timeout| !timeout.is_zero());
+    match state.manager.probe(&params.module, &params.target, timeout).await {
+        Ok(text) => ([(header::CONTENT_TYPE, EXPOSITION_CONTENT_TYPE)], text).into_response(),
+        Err(message) => (StatusCode::BAD_REQUEST, Json(json!({ "error": message }))).into_response(),
+    }
+}
+
+async fn list_monitors(State(state): State<AdminState>) -> Json<Vec<Value>> {
+    Json(
+        state
+            .manager
+            .snapshot()
+            .iter()
+            .map(|(monitor, runtime)| monitor_json(monitor, runtime))
+            .collect(),
+    )
+}
+
+async fn get_monitor(
+    State(state): State<AdminState>,
+    Path((project_id, site_key)): Path<(String, String)>,
+) -> Response {
+    match state
+        .manager
+        .snapshot()
+        .iter()
+        .find(|(m, _)| m.project_id == project_id && m.site_key == site_key)
+    {
+        Some((monitor, runtime)) => Json(monitor_json(monitor, runtime)).into_response(),
+        None => not_found(),
+    }
+}
+
+async fn check_all(State(state): State<AdminState>) -> Json<Vec<Value>> {
+    Json(check_now(&state, Scope::All).await)
+}
+
+async fn check_project(State(state): State<AdminState>, Path(project_id): Path<String>) -> Json<Vec<Value>> {
+    Json(check_now(&state, Scope::Project(project_id)).await)
+}
+
+async fn check_monitor(
+    State(state): State<AdminState>,
+    Path(key): Path<(String, String)>,
+) -> Response {
+    match state.manager.is_paused(&key) {
+        None => return not_found(),
+        Some(true) => return (StatusCode::CONFLICT, Json(json!({ "error": "monitor is paused" }))).into_response(),
+        Some(false) => {}
+    }
+    match check_now(&state, Scope::Monitor(key)).await.pop() {
+        Some(result) => Json(result).into_response(),
+        None => not_found(),
+    }
+}
+
+async fn check_now(state: &AdminState, scope: Scope) -> Vec<Value> {
+    info!(%scope, "check now requested via admin API");
+    state
+        .manager
+        .check_now(&scope)
+        .await
+        .iter()
+        .map(|result| {
+            let mut value = result_json(result);
+            value["project_id"] = json!(result.project_id);
+            value["site_key"] = json!(result.site_key);
+            value
+        })
+        .collect()
+}
+
+async fn pause_monitor(State(state): State<AdminState>, Path(key): Path<(String, String)>) -> Response {
+    set_paused(&state, key, true).await
+}
+
+async fn resume_monitor(State(state): State<AdminState>, Path(key): Path<(String, String)>) -> Response {
+    set_paused(&state, key, false).await
+}
+
+/// Reports the resulting state, which stays paused on resume while config.json pauses the monitor.
+async fn set_paused(state: &AdminState, key: (String, String), paused: bool) -> Response {
+    match state.manager.set_paused(&key, paused).await {
+        Ok(Some(paused)) => Json(json!({ "project_id": key.0, "site_key": key.1, "paused": paused })).into_response(),
+        Ok(None) => not_found(),
+        Err(e) => {
+            error!(project = %key.0, site = %key.1, error = %e, "failed to store runtime pause");
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "error": "failed to store pause" }))).into_response()
+        }
+    }
+}
+
+fn not_found() -> Response {
+    (StatusCode::NOT_FOUND, Json(json!({ "error": "monitor not found" }))).into_response()
+}
+
+/// Resolved settings, confirmed state and latest check of one monitor, with config secrets masked.
+/// Header values, query values and request bodies are left out entirely since they often carry
+/// credentials that aren't config secrets; only their names and keys are shown.
+fn monitor_json(monitor: &ResolvedMonitor, runtime: &MonitorRuntime) -> Value {
+    let state = &runtime.state;
+    let mut value = json!({
+        "project_id": monitor.project_id,
+        "site_key": monitor.site_key,
+        "type": monitor.check.kind_str(),
+        "url": monitor.url,
+        "settings": {
+            "interval_sec": monitor.interval.as_secs(),
+            "timeout_sec": monitor.timeout.as_secs(),
+            "failures_before_down": monitor.failures_before_down,
+            "successes_before_up": monitor.successes_before_up,
+            "retry_count": monitor.retry_count,
+            "retry_interval_sec": monitor.retry_interval.as_secs(),
+            "response_warn_ms": monitor.response_warn_ms,
+            "response_critical_ms": monitor.response_critical_ms,
+            "notifiers": monitor.notifiers.iter().map(|n| n.target()).collect::<Vec<_>>(),
+            "maintenance": monitor.maintenance,
+            "check": check_json(&monitor.check),
+        },
+        "paused": runtime.paused,
+        "state": {
+            "is_up": state.is_up,
+            "is_degraded": state.is_degraded,
+            "consecutive_failures": state.consecutive_failures,
+            "consecutive_successes": state.consecutive_successes,
+            "consecutive_slow": state.consecutive_slow,
+            "consecutive_fast": state.consecutive_fast,
+        },
+        "last_result": runtime.last_result.as_ref().map(result_json),
+        "next_run_at": runtime.next_run_at,
+    });
+    secrets::redact_value(&mut value, &monitor.secrets);
+    value
+}
+
+fn check_json(check: &CheckSpec) -> Value {
+    match check {
+        CheckSpec::Http(http) => json!({
+            "http_method": http.http_method,
+            "expected_status_code": http.expected_status_code,
+            "headers": http.headers.iter().map(|(name, _)| name).collect::<Vec<_>>(),
+            "query": http.query.keys().collect::<Vec<_>>(),
+            "body": http.body.as_ref().map(body_json),
+            "expected_body": http.expected_body,
+            "assertions": http.assertions,
+            "tls_skip_verify": http.tls_skip_verify,
+            "fresh_connection": http.fresh_connection,
+            "cert_warn_days": http.cert.warn_days,
+            "cert_min_days": http.cert.min_days,
+        }),
+        CheckSpec::Tcp(tcp) => json!({
+            "host": tcp.host,
+            "port": tcp.port,
+            "send": tcp.send,
+            "expect": tcp.expect,
+        }),
+        CheckSpec::Icmp(icmp) => json!({
+            "host": icmp.host,
+            "ping_count": icmp.count,
+            "max_packet_loss_pct": icmp.max_packet_loss_pct,
+        }),
+        CheckSpec::Dns(dns) => json!({
+            "host": dns.name,
+            "record_type": dns.record_type,
+            "resolver": dns.resolver,
+            "expected_answers": dns.expected_answers,
+            "min_answers": dns.min_answers,
+        }),
+        CheckSpec::Tls(tls) => json!({
+            "host": tls.host,
+            "port": tls.port,
+            "tls_skip_verify": tls.tls_skip_verify,
+            "cert_warn_days": tls.cert.warn_days,
+            "cert_min_days": tls.cert.min_days,
+        }),
+    }
+}
+
+/// The shape of a request body: its kind, plus the top-level keys of a JSON object.
+fn body_json(body: &RequestBody) -> Value {
+    match body {
+        RequestBody::Raw(_) => json!({ "kind": "raw" }),
+        RequestBody::Json(Value::Object(map)) => json!({ "kind": "json", "keys": map.keys().collect::<Vec<_>>() }),
+        RequestBody::Json(_) => json!({ "kind": "json" }),
+    }
+}
+
+fn result_json(result: &CheckResult) -> Value {
+    json!({
+        "checked_at": result.checked_at,
+        "is_up": result.is_up,
+        "degraded": result.degraded,
+        "is_retry": result.is_retry,
+        "in_maintenance": result.in_maintenance,
+        "status_code": result.status_code,
+        "response_ms": result.response_ms,
+        "error_type": result.error_type.as_ref().map(|e| e.as_str()),
+        "error_message": result.error_message,
+        "timings": result.timings,
+        "ping": result.ping,
+        "dns_records": result.dns_records,
+        "tls": result.tls,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, test_monitor};
+    use crate::monitor::HttpClients;
+    use sqlx::postgres::PgPoolOptions;
+    use std::time::Duration;
+    use tokio::sync::watch;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Admin API over a manager whose database is unreachable; checks still run, their writes just fail.
+    async fn start(monitors: Vec<ResolvedMonitor>) -> String {
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://upmon@127.0.0.1:1/upmon")
+            .unwrap();
+        let (retention_tx, _) = watch::channel(90);
+        let manager = Arc::new(MonitorManager::new(pool, HttpClients::new(Duration::from_secs(5)), retention_tx));
+        let mut config: Config = serde_json::from_value(json!({
+            "defaults": { "interval_sec": 60, "timeout_sec": 5 },
+            "projects": [],
+        }))
+        .unwrap();
+        manager.set_probe_modules(config.resolve_probe_modules());
+        manager.start_initial(monitors);
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let base = format!("http://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve(listener, "s3cr3t".into(), manager));
+        base
+    }
+
+    #[tokio::test]
+    async fn rejects_missing_or_wrong_token() {
+        let base = start(Vec::new()).await;
+        let client = reqwest::Client::new();
+        let response = client.get(format!("{base}/status")).send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client.get(format!("{base}/status")).bearer_auth("wrong").send().await.unwrap();
+        assert_eq!(response.status(), 401);
+        let response = client.get(format!("{base}/status")).bearer_auth("s3cr3t").send().await.unwrap();
+        assert_eq!(response.status(), 200);
+        let body: Value = response.json().await.unwrap();
+        assert_eq!(body["monitor_count"], 0);
+    }
+
+    #[tokio::test]
+    async fn reports_monitor_settings_and_last_result() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+        let mut monitor = test_monitor(&format!("{}/health", server.uri()));
+        monitor.secrets = vec!["health".into()];
+        if let CheckSpec::Http(http) = &mut monitor.check {
+            http.headers = vec![("Authorization".into(), "Bearer token".into())];
+            http.query = [("api_key".to_string(), "literal-key".to_string())].into();
+            http.body = Some(RequestBody::Json(json!({ "password": "hunter2" })));
+        }
+        let base = start(vec![monitor]).await;
+        let client = reqwest::Client::new();
+
+        let mut body = Value::Null;
+        for _ in 0..50 {
+            body = client
+                .get(format!("{base}/monitors/test-proj/test-site"))
+                .bearer_auth("s3cr3t")
+                .send()
+                .await
+                .unwrap()
+                .json()
+                .await
+                .unwrap();
+            if !body["last_result"].is_null() {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        assert_eq!(body["type"], "http");
+        assert_eq!(body["url"], format!("{}/<redacted>", server.uri()));
+        assert_eq!(body["settings"]["interval_sec"], 60);
+        assert_eq!(body["settings"]["check"]["headers"], json!(["Authorization"]));
+        assert_eq!(body["settings"]["check"]["query"], json!(["api_key"]));
+        assert_eq!(body["settings"]["check"]["body"], json!({ "kind": "json", "keys": ["password"] }));
+        assert_eq!(body["last_result"]["is_up"], true);
+        assert_eq!(body["last_result"]["status_code"], 200);
+        assert!(body["next_run_at"].is_string());
+
+        let response = client.get(format!("{base}/metrics")).bearer_auth("s3cr3t").send().await.unwrap();
+        assert_eq!(response.headers()["content-type"], EXPOSITION_CONTENT_TYPE);
+        let text = response.text().await.unwrap();
+        assert!(text.contains(r#"upmon_up{project_id="test-proj",site_key="test-site"} 1"#));
+        assert!(text.contains(r#"upmon_db_insert_failures_total{table="monitor_checks"} 1"#));
+
+        let response = client
+            .get(format!("{base}/monitors/test-proj/missing"))
+            .bearer_auth("s3cr3t")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn check_now_runs_once_for_concurrent_triggers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(300)))
+            .mount(&server)
+            .await;
+        let base = start(vec![test_monitor(&server.uri())]).await;
+        let client = reqwest::Client::new();
+        let trigger = || async {
+            let response = client
+                .post(format!("{base}/monitors/test-proj/test-site/check"))
+                .bearer_auth("s3cr3t")
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(response.status(), 200);
+            response.json::<Value>().await.unwrap()
+        };
+
+        // The first check starts immediately; both triggers arrive while it's in flight.
+        let (a, b) = tokio::join!(trigger(), trigger());
+        assert_eq!(a["checked_at"], b["checked_at"]);
+        assert_eq!(a["site_key"], "test-site");
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        let c = trigger().await;
+        assert_ne!(c["checked_at"], a["checked_at"]);
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+
+        let response = client.post(format!("{base}/projects/other/check")).bearer_auth("s3cr3t").send().await.unwrap();
+        assert_eq!(response.json::<Value>().await.unwrap(), json!([]));
+    }
+
+    #[tokio::test]
+    async fn probe_checks_target_with_module() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(503)).mount(&server).await;
+        let base = start(Vec::new()).await;
+        let client = reqwest::Client::new();
+        let probe = |query: String| {
+            client
+                .get(format!("{base}/probe?{query}"))
+                .header("X-Prometheus-Scrape-Timeout-Seconds", "10")
+                .bearer_auth("s3cr3t")
+                .send()
+        };
+
+        let response = probe(format!("target={}", server.uri())).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["content-type"], EXPOSITION_CONTENT_TYPE);
+        let text = response.text().await.unwrap();
+        assert!(text.lines().any(|l| l == "probe_success 0"), "{text}");
+        assert!(text.lines().any(|l| l == "probe_http_status_code 503"), "{text}");
+
+        let response = probe(format!("target={}&module=missing", server.uri())).await.unwrap();
+        assert_eq!(response.status(), 400);
+        let response = probe("module=http_2xx".into()).await.unwrap();
+        assert_eq!(response.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn paused_monitor_is_not_checked() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+        let mut monitor = test_monitor(&server.uri());
+        monitor.paused = true;
+        let base = start(vec![monitor]).await;
+        let client = reqwest::Client::new();
+
+        let response = client
+            .post(format!("{base}/monitors/test-proj/test-site/check"))
+            .bearer_auth("s3cr3t")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 409);
+        let response = client.post(format!("{base}/check")).bearer_auth("s3cr3t").send().await.unwrap();
+        assert_eq!(response.json::<Value>().await.unwrap(), json!([]));
+
+        let body: Value = client
+            .get(format!("{base}/monitors/test-proj/test-site"))
+            .bearer_auth("s3cr3t")
+            .send()
+            .await
+            .unwrap()
+            .json()
+            .await
+            .unwrap();
+        assert_eq!(body["paused"], true);
+        assert!(body["next_run_at"].is_null());
+        assert!(server.received_requests().await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,279 @@
+use reqwest::{Client, Url};
+use serde_json::json;
+
+use super::{Alert, AlertEvent, AttemptResult, check_response, format_duration};
+use crate::config::{DiscordConfig, MatrixConfig, SlackConfig, TelegramConfig};
+
+const RED: u32 = 0xd32f2f;
+const GREEN: u32 = 0x2e7d32;
+const AMBER: u32 = 0xf9a825;
+
+fn colour(alert: &Alert) -> u32 {
+    match alert.event {
+        AlertEvent::Down => RED,
+        AlertEvent::Degraded => AMBER,
+        AlertEvent::Recovered | AlertEvent::Restored => GREEN,
+    }
+}
+
+fn title(alert: &Alert) -> String {
+    match alert.event {
+        AlertEvent::Down => format!("{}/{} is DOWN", alert.project_id, alert.site_key),
+        AlertEvent::Recovered => format!("{}/{} recovered", alert.project_id, alert.site_key),
+        AlertEvent::Degraded => format!("{}/{} is SLOW", alert.project_id, alert.site_key),
+        AlertEvent::Restored => format!("{}/{} is fast again", alert.project_id, alert.site_key),
+    }
+}
+
+/// Error detail for outages and slowness, their duration once they end.
+fn detail(alert: &Alert) -> String {
+    match alert.event {
+        AlertEvent::Down | AlertEvent::Degraded => match (alert.error_type, &alert.error_message) {
+            (Some(t), Some(m)) => format!("{t}: {m}"),
+            (Some(t), None) => t.to_string(),
+            (None, Some(m)) => m.clone(),
+            (None, None) => "check failed".to_string(),
+        },
+        AlertEvent::Recovered => match alert.downtime {
+            Some(d) => format!("down for {}", format_duration(d)),
+            None => "back up".to_string(),
+        },
+        AlertEvent::Restored => match alert.downtime {
+            Some(d) => format!("slow for {}", format_duration(d)),
+            None => "responding normally".to_string(),
+        },
+    }
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+pub async fn send_slack(client: &Client, config: &SlackConfig, alert: &Alert) -> AttemptResult {
+    let payload = json!({
+        "text": title(alert),
+        "attachments": [{
+            "color": format!("#{:06x}", colour(alert)),
+            "title": title(alert),
+            "title_link": alert.url,
+            "text": detail(alert),
+            "ts": alert.checked_at.timestamp(),
+        }],
+    });
+    check_response(client.post(&config.webhook_url).json(&payload).send().await)
+}
+
+pub async fn send_discord(client: &Client, config: &DiscordConfig, alert: &Alert) -> AttemptResult {
+    let payload = json!({
+        "embeds": [{
+            "title": title(alert),
+            "url": alert.url,
+            "description": detail(alert),
+            "color": colour(alert),
+            "timestamp": alert.checked_at.to_rfc3339(),
+        }],
+    });
+    check_response(client.post(&config.webhook_url).json(&payload).send().await)
+}
+
+pub async fn send_telegram(client: &Client, config: &TelegramConfig, alert: &Alert) -> AttemptResult {
+    let icon = match alert.event {
+        AlertEvent::Down => "🔴",
+        AlertEvent::Degraded => "🟡",
+        AlertEvent::Recovered | AlertEvent::Restored => "🟢",
+    };
+    let text = format!(
+        "{icon} <b>{}</b>\n<a href=\"{}\">{}</a>\n{}",
+        escape_html(&title(alert)),
+        escape_html(&alert.url),
+        escape_html(&alert.url),
+        escape_html(&detail(alert)),
+    );
+    let url = format!("{}/bot{}/sendMessage", config.api_url.trim_end_matches('/'), config.bot_token);
+    let payload = json!({
+        "chat_id": config.chat_id,
+        "text": text,
+        "parse_mode": "HTML",
+        "disable_web_page_preview": true,
+    });
+    check_response(client.post(url).json(&payload).send().await)
+}
+
+pub async fn send_matrix(client: &Client, config: &MatrixConfig, alert: &Alert) -> AttemptResult {
+    let mut url = Url::parse(&config.homeserver).map_err(|e| (None, format!("invalid homeserver: {e}")))?;
+    // Transaction ids only need to be unique per access token.
+    let txn_id = format!(
+        "upmon-{}-{}-{}",
+        alert.checked_at.timestamp_millis(),
+        alert.event.as_str(),
+        alert.site_key
+    );
+    url.path_segments_mut()
+        .map_err(|_| (None, "invalid homeserver: cannot be a base".to_string()))?
+        .pop_if_empty()
+        .extend(["_matrix", "client", "v3", "rooms", &config.room_id, "send", "m.room.message", &txn_id]);
+
+    let body = format!("{}\n{}\n{}", title(alert), alert.url, detail(alert));
+    let formatted_body = format!(
+        "<font color=\"#{:06x}\"><b>{}</b></font><br><a href=\"{}\">{}</a><br>{}",
+        colour(alert),
+        escape_html(&title(alert)),
+        escape_html(&alert.url),
+        escape_html(&alert.url),
+        escape_html(&detail(alert)),
+    );
+    let payload = json!({
+        "msgtype": "m.text",
+        "body": body,
+        "format": "org.matrix.custom.html",
+        "formatted_body": formatted_body,
+    });
+    check_response(
+        client
+            .put(url)
+            .bearer_auth(&config.access_token)
+            .json(&payload)
+            .send()
+            .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+    use std::time::Duration;
+    use wiremock::matchers::{body_json, body_partial_json, header, method, path, path_regex};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn make_alert(event: AlertEvent) -> Alert {
+        Alert {
+            event,
+            project_id: "proj".into(),
+            site_key: "prod".into(),
+            url: "https://example.com/health?a=1&b=2".into(),
+            error_type: Some("unexpected_status"),
+            error_message: Some("<html>502</html>".into()),
+            checked_at: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().to_utc(),
+            downtime: Some(Duration::from_secs(300)),
+        }
+    }
+
+    #[test]
+    fn detail_depends_on_event() {
+        assert_eq!(detail(&make_alert(AlertEvent::Down)), "unexpected_status: <html>502</html>");
+        assert_eq!(detail(&make_alert(AlertEvent::Recovered)), "down for 5m 0s");
+        assert_eq!(detail(&make_alert(AlertEvent::Restored)), "slow for 5m 0s");
+    }
+
+    #[tokio::test]
+    async fn slack_attachment_has_colour_and_link() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/services/T/B/X"))
+            .and(body_json(json!({
+                "text": "proj/prod is DOWN",
+                "attachments": [{
+                    "color": "#d32f2f",
+                    "title": "proj/prod is DOWN",
+                    "title_link": "https://example.com/health?a=1&b=2",
+                    "text": "unexpected_status: <html>502</html>",
+                    "ts": 1767323045,
+                }],
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = SlackConfig { webhook_url: format!("{}/services/T/B/X", server.uri()) };
+        let outcome = send_slack(&Client::new(), &config, &make_alert(AlertEvent::Down)).await;
+        assert_eq!(outcome.unwrap(), Some(200));
+    }
+
+    #[tokio::test]
+    async fn discord_embed_uses_integer_colour() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/webhooks/1/abc"))
+            .and(body_partial_json(json!({
+                "embeds": [{ "title": "proj/prod recovered", "color": GREEN, "description": "down for 5m 0s" }],
+            })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = DiscordConfig { webhook_url: format!("{}/api/webhooks/1/abc", server.uri()) };
+        let outcome = send_discord(&Client::new(), &config, &make_alert(AlertEvent::Recovered)).await;
+        assert_eq!(outcome.unwrap(), Some(204));
+    }
+
+    #[tokio::test]
+    async fn telegram_escapes_html() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/bot123:abc/sendMessage"))
+            .and(body_json(json!({
+                "chat_id": "-10042",
+                "text": "🔴 <b>proj/prod is DOWN</b>\n\
+                         <a href=\"https://example.com/health?a=1&amp;b=2\">https://example.com/health?a=1&amp;b=2</a>\n\
+                         unexpected_status: &lt;html&gt;502&lt;/html&gt;",
+                "parse_mode": "HTML",
+                "disable_web_page_preview": true,
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = TelegramConfig {
+            bot_token: "123:abc".into(),
+            chat_id: "-10042".into(),
+            api_url: format!("{}/", server.uri()),
+        };
+        let outcome = send_telegram(&Client::new(), &config, &make_alert(AlertEvent::Down)).await;
+        assert_eq!(outcome.unwrap(), Some(200));
+    }
+
+    #[tokio::test]
+    async fn matrix_puts_room_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("PUT"))
+            .and(path_regex(r"^/_matrix/client/v3/rooms/!room:example\.org/send/m\.room\.message/upmon-\d+-down-prod$"))
+            .and(header("authorization", "Bearer secret"))
+            .and(body_partial_json(json!({
+                "msgtype": "m.text",
+                "format": "org.matrix.custom.html",
+                "body": "proj/prod is DOWN\nhttps://example.com/health?a=1&b=2\nunexpected_status: <html>502</html>",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "event_id": "$1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = MatrixConfig {
+            homeserver: server.uri(),
+            access_token: "secret".into(),
+            room_id: "!room:example.org".into(),
+        };
+        let outcome = send_matrix(&Client::new(), &config, &make_alert(AlertEvent::Down)).await;
+        assert_eq!(outcome.unwrap(), Some(200));
+    }
+
+    #[tokio::test]
+    async fn error_status_is_a_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let config = SlackConfig { webhook_url: server.uri() };
+        let (status, message) = send_slack(&Client::new(), &config, &make_alert(AlertEvent::Down))
+            .await
+            .unwrap_err();
+        assert_eq!(status, Some(404));
+        assert!(message.contains("404"));
+    }
+}
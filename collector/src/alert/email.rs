@@ -0,0 +1,177 @@
+use std::time::Duration;
+
+use lettre::message::Mailbox;
+use lettre::message::header::ContentType;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+
+use super::{Alert, AttemptResult};
+use crate::config::{EmailConfig, SmtpConfig, SmtpTls};
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+const DEFAULT_SUBJECT: &str = "[upmon] {{project_id}}/{{site_key}} is {{event}}";
+
+const DEFAULT_BODY: &str = "\
+{{project_id}}/{{site_key}} is {{event}}.
+
+URL: {{url}}
+Checked at: {{checked_at}}
+Error: {{error_type}} {{error_message}}
+Downtime: {{downtime}}
+";
+
+pub async fn send(config: &EmailConfig, alert: &Alert) -> AttemptResult {
+    let Some(smtp) = &config.smtp else {
+        return Err((None, "no smtp server configured".into()));
+    };
+    let message = build_message(smtp, config, alert).map_err(|e| (None, e))?;
+    let transport = build_transport(smtp).map_err(|e| (None, e.to_string()))?;
+    transport.send(message).await.map_err(|e| (None, e.to_string()))?;
+    Ok(None)
+}
+
+fn build_message(smtp: &SmtpConfig, config: &EmailConfig, alert: &Alert) -> Result<Message, String> {
+    let from: Mailbox = smtp.from.parse().map_err(|e| format!("invalid from address: {e}"))?;
+    let mut builder = Message::builder()
+        .from(from)
+        .subject(alert.render(config.subject.as_deref().unwrap_or(DEFAULT_SUBJECT)))
+        .header(ContentType::TEXT_PLAIN);
+    for to in &config.to {
+        builder = builder.to(to.parse().map_err(|e| format!("invalid recipient '{to}': {e}"))?);
+    }
+    builder
+        .body(alert.render(config.body.as_deref().unwrap_or(DEFAULT_BODY)))
+        .map_err(|e| e.to_string())
+}
+
+fn build_transport(
+    smtp: &SmtpConfig,
+) -> Result<AsyncSmtpTransport<Tokio1Executor>, lettre::transport::smtp::Error> {
+    let builder = match smtp.tls {
+        SmtpTls::Starttls => AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)?,
+        SmtpTls::Implicit => AsyncSmtpTransport::<Tokio1Executor>::relay(&smtp.host)?,
+        SmtpTls::None => AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host),
+    };
+    let mut builder = builder.port(smtp.port()).timeout(Some(SMTP_TIMEOUT));
+    if let Some(username) = &smtp.username {
+        builder = builder.credentials(Credentials::new(
+            username.clone(),
+            smtp.password.clone().unwrap_or_default(),
+        ));
+    }
+    Ok(builder.build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::AlertEvent;
+    use chrono::Utc;
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+    use tokio::net::TcpListener;
+    use tokio::sync::oneshot;
+
+    struct Received {
+        rcpt_to: Vec<String>,
+        data: String,
+    }
+
+    /// Minimal plaintext SMTP server that accepts one message.
+    async fn start_sink() -> (u16, oneshot::Receiver<Received>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let (tx, rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let (read, mut write) = stream.into_split();
+            let mut lines = BufReader::new(read).lines();
+            let mut received = Received { rcpt_to: Vec::new(), data: String::new() };
+            write.write_all(b"220 sink ESMTP\r\n").await.unwrap();
+            while let Some(line) = lines.next_line().await.unwrap() {
+                let upper = line.to_ascii_uppercase();
+                let reply: &[u8] = if upper.starts_with("EHLO") || upper.starts_with("HELO") {
+                    b"250 sink\r\n"
+                } else if upper.starts_with("RCPT TO:") {
+                    received.rcpt_to.push(line[8..].trim().trim_matches(['<', '>']).to_string());
+                    b"250 OK\r\n"
+                } else if upper.starts_with("DATA") {
+                    write.write_all(b"354 go ahead\r\n").await.unwrap();
+                    while let Some(data_line) = lines.next_line().await.unwrap() {
+                        if data_line == "." {
+                            break;
+                        }
+                        received.data.push_str(&data_line);
+                        received.data.push('\n');
+                    }
+                    b"250 queued\r\n"
+                } else if upper.starts_with("QUIT") {
+                    write.write_all(b"221 bye\r\n").await.unwrap();
+                    break;
+                } else {
+                    b"250 OK\r\n"
+                };
+                write.write_all(reply).await.unwrap();
+            }
+            let _ = tx.send(received);
+        });
+        (port, rx)
+    }
+
+    fn make_alert() -> Alert {
+        Alert {
+            event: AlertEvent::Recovered,
+            project_id: "proj".into(),
+            site_key: "prod".into(),
+            url: "https://example.com/health".into(),
+            error_type: None,
+            error_message: None,
+            checked_at: Utc::now(),
+            downtime: Some(Duration::from_secs(125)),
+        }
+    }
+
+    fn make_smtp(port: u16) -> SmtpConfig {
+        SmtpConfig {
+            host: "127.0.0.1".into(),
+            port: Some(port),
+            tls: SmtpTls::None,
+            username: None,
+            password: None,
+            from: "upmon <upmon@example.com>".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn sends_rendered_message_to_all_recipients() {
+        let (port, received) = start_sink().await;
+        let config = EmailConfig {
+            to: vec!["ops@example.com".into(), "dev@example.com".into()],
+            subject: Some("{{site_key}} back after {{downtime}}".into()),
+            body: None,
+            smtp: Some(make_smtp(port)),
+        };
+
+        let outcome = send(&config, &make_alert()).await;
+        assert!(outcome.is_ok(), "{outcome:?}");
+
+        let received = received.await.unwrap();
+        assert_eq!(received.rcpt_to, vec!["ops@example.com", "dev@example.com"]);
+        assert!(received.data.contains("Subject: prod back after 2m 5s"));
+        assert!(received.data.contains("proj/prod is recovered."));
+        assert!(received.data.contains("URL: https://example.com/health"));
+    }
+
+    #[tokio::test]
+    async fn missing_smtp_config_fails() {
+        let config = EmailConfig {
+            to: vec!["ops@example.com".into()],
+            subject: None,
+            body: None,
+            smtp: None,
+        };
+        let (status, message) = send(&config, &make_alert()).await.unwrap_err();
+        assert_eq!(status, None);
+        assert!(message.contains("no smtp server"));
+    }
+}
@@ -0,0 +1,231 @@
+mod chat;
+mod email;
+mod webhook;
+
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use reqwest::Client;
+use sqlx::PgPool;
+use tokio::time;
+use tracing::{error, info, warn};
+
+use crate::config::{NotifierConfig, NotifierKind};
+use crate::db;
+use crate::models::CheckResult;
+use crate::state::Transition;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AlertEvent {
+    Down,
+    Recovered,
+    Degraded,
+    Restored,
+}
+
+impl AlertEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AlertEvent::Down => "down",
+            AlertEvent::Recovered => "recovered",
+            AlertEvent::Degraded => "degraded",
+            AlertEvent::Restored => "restored",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Alert {
+    pub event: AlertEvent,
+    pub project_id: String,
+    pub site_key: String,
+    pub url: String,
+    pub error_type: Option<&'static str>,
+    pub error_message: Option<String>,
+    pub checked_at: DateTime<Utc>,
+    /// Length of the outage, or of the slow spell for `Restored`; only known once it ends.
+    pub downtime: Option<Duration>,
+}
+
+impl Alert {
+    pub fn new(transition: Transition, result: &CheckResult, downtime: Option<Duration>) -> Self {
+        Self {
+            event: match transition {
+                Transition::Down => AlertEvent::Down,
+                Transition::Up => AlertEvent::Recovered,
+                Transition::Degraded => AlertEvent::Degraded,
+                Transition::Restored => AlertEvent::Restored,
+            },
+            project_id: result.project_id.clone(),
+            site_key: result.site_key.clone(),
+            url: result.url.clone(),
+            error_type: result.error_type.as_ref().map(|e| e.as_str()),
+            error_message: result.error_message.clone(),
+            checked_at: result.checked_at,
+            downtime,
+        }
+    }
+
+    /// Values available to notifier templates as `{{name}}`.
+    fn placeholders(&self) -> [(&'static str, String); 9] {
+        [
+            ("event", self.event.as_str().to_string()),
+            ("project_id", self.project_id.clone()),
+            ("site_key", self.site_key.clone()),
+            ("url", self.url.clone()),
+            ("error_type", self.error_type.unwrap_or_default().to_string()),
+            ("error_message", self.error_message.clone().unwrap_or_default()),
+            ("checked_at", self.checked_at.to_rfc3339()),
+            ("downtime_sec", self.downtime.map(|d| d.as_secs().to_string()).unwrap_or_default()),
+            ("downtime", self.downtime.map(format_duration).unwrap_or_default()),
+        ]
+    }
+
+    pub fn render(&self, template: &str) -> String {
+        let mut out = template.to_string();
+        for (name, value) in self.placeholders() {
+            out = out.replace(&format!("{{{{{name}}}}}"), &value);
+        }
+        out
+    }
+}
+
+/// Human-readable duration such as `1h 2m 3s`.
+pub fn format_duration(d: Duration) -> String {
+    let secs = d.as_secs();
+    let (h, m, s) = (secs / 3600, secs % 3600 / 60, secs % 60);
+    if h > 0 {
+        format!("{h}h {m}m {s}s")
+    } else if m > 0 {
+        format!("{m}m {s}s")
+    } else {
+        format!("{s}s")
+    }
+}
+
+pub struct Delivery {
+    pub success: bool,
+    pub attempts: u32,
+    pub status_code: Option<u16>,
+    pub error_message: Option<String>,
+}
+
+/// Outcome of a single delivery attempt: the HTTP status (if any) or an error.
+/// Email deliveries never carry a status.
+type AttemptResult = Result<Option<u16>, (Option<u16>, String)>;
+
+/// Maps an HTTP delivery to an attempt outcome; any non-2xx status counts as a failure.
+/// Errors leave out the URL, which may embed a token.
+fn check_response(response: reqwest::Result<reqwest::Response>) -> AttemptResult {
+    let response = response.map_err(|e| (None, e.without_url().to_string()))?;
+    let status = response.status();
+    if status.is_success() {
+        Ok(Some(status.as_u16()))
+    } else {
+        Err((Some(status.as_u16()), format!("server returned {status}")))
+    }
+}
+
+/// Sends `alert` to every notifier in the background; deliveries are logged to `alert_deliveries`.
+pub fn dispatch(pool: &PgPool, client: &Client, notifiers: &[NotifierConfig], alert: Alert) {
+    for notifier in notifiers {
+        let (pool, client, notifier, alert) = (pool.clone(), client.clone(), notifier.clone(), alert.clone());
+        tokio::spawn(async move {
+            let sent_at = Utc::now();
+            let delivery = deliver(&client, &notifier, &alert).await;
+            if let Err(e) = db::insert_alert_delivery(&pool, sent_at, &alert, &notifier, &delivery).await {
+                error!(
+                    project = alert.project_id,
+                    site = alert.site_key,
+                    error = %e,
+                    "failed to record alert delivery"
+                );
+            }
+        });
+    }
+}
+
+/// Delivers one alert, retrying with exponential backoff up to `max_attempts` times.
+pub async fn deliver(client: &Client, notifier: &NotifierConfig, alert: &Alert) -> Delivery {
+    let mut backoff = Duration::from_millis(notifier.backoff_ms);
+    let mut attempts = 0;
+    loop {
+        attempts += 1;
+        let outcome: AttemptResult = match &notifier.kind {
+            NotifierKind::Webhook(config) => webhook::send(client, config, alert).await,
+            NotifierKind::Email(config) => email::send(config, alert).await,
+            NotifierKind::Slack(config) => chat::send_slack(client, config, alert).await,
+            NotifierKind::Telegram(config) => chat::send_telegram(client, config, alert).await,
+            NotifierKind::Discord(config) => chat::send_discord(client, config, alert).await,
+            NotifierKind::Matrix(config) => chat::send_matrix(client, config, alert).await,
+        };
+        match outcome {
+            Ok(status_code) => {
+                info!(
+                    project = alert.project_id,
+                    site = alert.site_key,
+                    event = alert.event.as_str(),
+                    target = notifier.target(),
+                    attempts,
+                    "alert delivered"
+                );
+                return Delivery { success: true, attempts, status_code, error_message: None };
+            }
+            Err((status_code, message)) => {
+                warn!(
+                    project = alert.project_id,
+                    site = alert.site_key,
+                    target = notifier.target(),
+                    attempt = attempts,
+                    error = message,
+                    "alert delivery failed"
+                );
+                if attempts >= notifier.max_attempts {
+                    return Delivery { success: false, attempts, status_code, error_message: Some(message) };
+                }
+            }
+        }
+        time::sleep(backoff).await;
+        backoff *= 2;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn make_alert(event: AlertEvent) -> Alert {
+        Alert {
+            event,
+            project_id: "proj".into(),
+            site_key: "prod".into(),
+            url: "https://example.com/health".into(),
+            error_type: Some("timeout"),
+            error_message: Some("operation timed out".into()),
+            checked_at: DateTime::parse_from_rfc3339("2026-01-02T03:04:05Z").unwrap().to_utc(),
+            downtime: Some(Duration::from_secs(3723)),
+        }
+    }
+
+    #[test]
+    fn render_replaces_placeholders() {
+        let alert = make_alert(AlertEvent::Recovered);
+        assert_eq!(
+            alert.render("{{project_id}}/{{site_key}} {{event}} after {{downtime}} ({{downtime_sec}}s)"),
+            "proj/prod recovered after 1h 2m 3s (3723s)"
+        );
+    }
+
+    #[test]
+    fn render_leaves_unknown_placeholders() {
+        let alert = make_alert(AlertEvent::Down);
+        assert_eq!(alert.render("{{nope}} {{error_type}}"), "{{nope}} timeout");
+    }
+
+    #[test]
+    fn format_duration_units() {
+        assert_eq!(format_duration(Duration::from_secs(5)), "5s");
+        assert_eq!(format_duration(Duration::from_secs(65)), "1m 5s");
+        assert_eq!(format_duration(Duration::from_secs(7200)), "2h 0m 0s");
+    }
+}
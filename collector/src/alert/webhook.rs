@@ -0,0 +1,152 @@
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use super::{Alert, AttemptResult, check_response};
+use crate::config::WebhookConfig;
+
+pub async fn send(client: &Client, config: &WebhookConfig, alert: &Alert) -> AttemptResult {
+    let payload = match &config.template {
+        Some(template) => render_value(template, alert),
+        None => default_payload(alert),
+    };
+    check_response(client.post(&config.url).json(&payload).send().await)
+}
+
+fn default_payload(alert: &Alert) -> Value {
+    json!({
+        "event": alert.event.as_str(),
+        "project_id": alert.project_id,
+        "site_key": alert.site_key,
+        "url": alert.url,
+        "error_type": alert.error_type,
+        "error_message": alert.error_message,
+        "checked_at": alert.checked_at,
+        "downtime_sec": alert.downtime.map(|d| d.as_secs()),
+    })
+}
+
+/// Substitutes placeholders in every string of the template, keeping its JSON structure.
+fn render_value(template: &Value, alert: &Alert) -> Value {
+    match template {
+        Value::String(s) => Value::String(alert.render(s)),
+        Value::Array(items) => Value::Array(items.iter().map(|v| render_value(v, alert)).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), render_value(v, alert)))
+                .collect(),
+        ),
+        other => other.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::{AlertEvent, deliver};
+    use crate::config::{NotifierConfig, NotifierKind};
+    use chrono::Utc;
+    use std::time::Duration;
+    use wiremock::matchers::{body_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn make_alert() -> Alert {
+        Alert {
+            event: AlertEvent::Down,
+            project_id: "proj".into(),
+            site_key: "prod".into(),
+            url: "https://example.com/health".into(),
+            error_type: Some("timeout"),
+            error_message: Some("operation timed out".into()),
+            checked_at: Utc::now(),
+            downtime: None,
+        }
+    }
+
+    fn make_notifier(url: String, template: Option<Value>) -> NotifierConfig {
+        NotifierConfig {
+            name: None,
+            max_attempts: 3,
+            backoff_ms: 10,
+            kind: NotifierKind::Webhook(WebhookConfig { url, template }),
+        }
+    }
+
+    #[tokio::test]
+    async fn posts_rendered_template() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hook"))
+            .and(body_json(json!({
+                "text": "proj/prod is down: timeout",
+                "tags": ["upmon", "down"],
+                "priority": 1
+            })))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let notifier = make_notifier(
+            format!("{}/hook", server.uri()),
+            Some(json!({
+                "text": "{{project_id}}/{{site_key}} is {{event}}: {{error_type}}",
+                "tags": ["upmon", "{{event}}"],
+                "priority": 1
+            })),
+        );
+        let delivery = deliver(&Client::new(), &notifier, &make_alert()).await;
+
+        assert!(delivery.success);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.status_code, Some(200));
+    }
+
+    #[tokio::test]
+    async fn retries_until_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(503))
+            .up_to_n_times(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let notifier = make_notifier(server.uri(), None);
+        let delivery = deliver(&Client::new(), &notifier, &make_alert()).await;
+
+        assert!(delivery.success);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status_code, Some(204));
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        let notifier = make_notifier(server.uri(), None);
+        let delivery = deliver(&Client::new(), &notifier, &make_alert()).await;
+
+        assert!(!delivery.success);
+        assert_eq!(delivery.attempts, 3);
+        assert_eq!(delivery.status_code, Some(500));
+        assert!(delivery.error_message.unwrap().contains("500"));
+    }
+
+    #[test]
+    fn default_payload_has_monitor_fields() {
+        let mut alert = make_alert();
+        alert.downtime = Some(Duration::from_secs(90));
+        let payload = default_payload(&alert);
+        assert_eq!(payload["event"], "down");
+        assert_eq!(payload["site_key"], "prod");
+        assert_eq!(payload["downtime_sec"], 90);
+    }
+}
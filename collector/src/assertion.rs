@@ -0,0 +1,540 @@
+use std::fmt;
+
+use regex::Regex;
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// A condition an `http` response must meet. Every assertion is evaluated, and each one that
+/// fails is reported with the value actually found.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Assertion {
+    /// Every field of `value` must appear in the JSON body; extra fields are allowed.
+    JsonSubset { value: Value },
+    JsonPath {
+        path: String,
+        #[serde(default)]
+        op: Op,
+        value: Option<Value>,
+    },
+    BodyContains { value: String },
+    BodyRegex { pattern: String },
+    Header {
+        name: String,
+        #[serde(default)]
+        op: Op,
+        value: Option<Value>,
+    },
+    /// Codes such as `204`, classes such as `"2xx"` or ranges such as `"200-299"`.
+    Status {
+        #[serde(rename = "in")]
+        codes: Vec<StatusMatch>,
+    },
+}
+
+/// An assertion ready to evaluate, its regex compiled once when the config is resolved.
+/// Compares and serializes as the assertion it came from.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(from = "Assertion", into = "Assertion")]
+pub struct ResolvedAssertion {
+    assertion: Assertion,
+    /// The `body_regex` pattern or the operand of `matches`; `None` when invalid, which validation reports.
+    regex: Option<Regex>,
+}
+
+impl ResolvedAssertion {
+    pub fn is_status(&self) -> bool {
+        self.assertion.is_status()
+    }
+}
+
+impl From<Assertion> for ResolvedAssertion {
+    fn from(assertion: Assertion) -> Self {
+        let pattern = match &assertion {
+            Assertion::BodyRegex { pattern } => Some(pattern),
+            Assertion::JsonPath { op: Op::Matches, value: Some(Value::String(pattern)), .. }
+            | Assertion::Header { op: Op::Matches, value: Some(Value::String(pattern)), .. } => Some(pattern),
+            _ => None,
+        };
+        let regex = pattern.and_then(|pattern| Regex::new(pattern).ok());
+        Self { assertion, regex }
+    }
+}
+
+impl From<ResolvedAssertion> for Assertion {
+    fn from(resolved: ResolvedAssertion) -> Self {
+        resolved.assertion
+    }
+}
+
+impl PartialEq for ResolvedAssertion {
+    fn eq(&self, other: &Self) -> bool {
+        self.assertion == other.assertion
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum Op {
+    #[default]
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Contains,
+    Matches,
+    Exists,
+}
+
+impl Op {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Op::Eq => "eq",
+            Op::Ne => "ne",
+            Op::Lt => "lt",
+            Op::Le => "le",
+            Op::Gt => "gt",
+            Op::Ge => "ge",
+            Op::Contains => "contains",
+            Op::Matches => "matches",
+            Op::Exists => "exists",
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum StatusMatch {
+    Code(u16),
+    Pattern(String),
+}
+
+impl StatusMatch {
+    /// Inclusive bounds of the codes this matches.
+    fn bounds(&self) -> Result<(u16, u16), String> {
+        match self {
+            StatusMatch::Code(code) => Ok((*code, *code)),
+            StatusMatch::Pattern(pattern) => {
+                let invalid = || format!("invalid status pattern {pattern:?}, expected e.g. \"2xx\" or \"200-299\"");
+                if let Some(class) = pattern.strip_suffix("xx") {
+                    let class: u16 = class.parse().map_err(|_| invalid())?;
+                    if !(1..=5).contains(&class) {
+                        return Err(invalid());
+                    }
+                    return Ok((class * 100, class * 100 + 99));
+                }
+                let (low, high) = pattern.split_once('-').ok_or_else(invalid)?;
+                let low: u16 = low.trim().parse().map_err(|_| invalid())?;
+                let high: u16 = high.trim().parse().map_err(|_| invalid())?;
+                if low > high {
+                    return Err(invalid());
+                }
+                Ok((low, high))
+            }
+        }
+    }
+}
+
+impl fmt::Display for StatusMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatusMatch::Code(code) => write!(f, "{code}"),
+            StatusMatch::Pattern(pattern) => f.write_str(pattern),
+        }
+    }
+}
+
+/// The parts of a response assertions look at.
+pub struct Response<'a> {
+    pub status: u16,
+    pub headers: &'a HeaderMap,
+    pub body: &'a str,
+}
+
+/// An assertion that did not hold, with what the response contained instead.
+pub struct Failure {
+    pub index: usize,
+    pub description: String,
+    pub actual: String,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "assertions[{}] {} failed, got {}", self.index, self.description, self.actual)
+    }
+}
+
+impl Assertion {
+    pub fn is_status(&self) -> bool {
+        matches!(self, Assertion::Status { .. })
+    }
+
+    /// Problems found before any response is seen: bad patterns, paths and operands.
+    pub fn check(&self) -> Option<String> {
+        match self {
+            Assertion::JsonSubset { .. } | Assertion::BodyContains { .. } => None,
+            Assertion::JsonPath { path, op, value } => {
+                parse_path(path).err().or_else(|| check_operand(*op, value.as_ref()))
+            }
+            Assertion::BodyRegex { pattern } => Regex::new(pattern).err().map(|e| format!("invalid regex: {e}")),
+            Assertion::Header { name, op, value } => {
+                if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                    Some(format!("invalid header name {name:?}"))
+                } else {
+                    check_operand(*op, value.as_ref())
+                }
+            }
+            Assertion::Status { codes } => {
+                if codes.is_empty() {
+                    return Some("status assertion needs at least one code".into());
+                }
+                codes.iter().find_map(|code| code.bounds().err())
+            }
+        }
+    }
+
+    fn describe(&self) -> String {
+        let operand = |op: &Op, value: &Option<Value>| match value {
+            Some(value) if *op != Op::Exists => format!("{} {value}", op.as_str()),
+            _ => op.as_str().to_string(),
+        };
+        match self {
+            Assertion::JsonSubset { value } => format!("json subset {value}"),
+            Assertion::JsonPath { path, op, value } => format!("{path} {}", operand(op, value)),
+            Assertion::BodyContains { value } => format!("body contains {value:?}"),
+            Assertion::BodyRegex { pattern } => format!("body matches /{pattern}/"),
+            Assertion::Header { name, op, value } => format!("header {name} {}", operand(op, value)),
+            Assertion::Status { codes } => {
+                let codes: Vec<String> = codes.iter().map(|c| c.to_string()).collect();
+                format!("status in [{}]", codes.join(", "))
+            }
+        }
+    }
+
+    /// `Err` holds the actual value that made the assertion fail. `regex` is the compiled pattern, if any.
+    fn evaluate(&self, response: &Response, json: &Result<Value, String>, regex: Option<&Regex>) -> Result<(), String> {
+        match self {
+            Assertion::JsonSubset { value } => {
+                let actual = json.as_ref().map_err(Clone::clone)?;
+                subset(value, actual, "$".to_string()).map_err(|(path, found)| format!("{found} at {path}"))
+            }
+            Assertion::JsonPath { path, op, value } => {
+                let actual = json.as_ref().map_err(Clone::clone)?;
+                let segments = parse_path(path)?;
+                compare(select(actual, &segments), *op, value.as_ref(), regex)
+            }
+            Assertion::BodyContains { value } => {
+                if response.body.contains(value.as_str()) {
+                    Ok(())
+                } else {
+                    Err(preview(response.body))
+                }
+            }
+            Assertion::BodyRegex { .. } => {
+                let re = regex.ok_or("invalid regex")?;
+                if re.is_match(response.body) {
+                    Ok(())
+                } else {
+                    Err(preview(response.body))
+                }
+            }
+            Assertion::Header { name, op, value } => {
+                let actual = response
+                    .headers
+                    .get(name.as_str())
+                    .map(|v| Value::String(String::from_utf8_lossy(v.as_bytes()).into_owned()));
+                compare(actual.as_ref(), *op, value.as_ref(), regex)
+            }
+            Assertion::Status { codes } => {
+                let matched = codes
+                    .iter()
+                    .any(|code| code.bounds().is_ok_and(|(low, high)| (low..=high).contains(&response.status)));
+                if matched {
+                    Ok(())
+                } else {
+                    Err(response.status.to_string())
+                }
+            }
+        }
+    }
+}
+
+/// Evaluates every assertion, parsing the body as JSON at most once.
+pub fn evaluate(assertions: &[ResolvedAssertion], response: &Response) -> Vec<Failure> {
+    let json = serde_json::from_str::<Value>(response.body).map_err(|_| format!("non-JSON body {}", preview(response.body)));
+    assertions
+        .iter()
+        .enumerate()
+        .filter_map(|(index, ResolvedAssertion { assertion, regex })| {
+            assertion.evaluate(response, &json, regex.as_ref()).err().map(|actual| Failure {
+                index,
+                description: assertion.describe(),
+                actual,
+            })
+        })
+        .collect()
+}
+
+const PREVIEW_CHARS: usize = 100;
+
+fn preview(body: &str) -> String {
+    if body.chars().count() <= PREVIEW_CHARS {
+        format!("{body:?}")
+    } else {
+        format!("{:?}...", body.chars().take(PREVIEW_CHARS).collect::<String>())
+    }
+}
+
+fn check_operand(op: Op, value: Option<&Value>) -> Option<String> {
+    match (op, value) {
+        (Op::Exists, _) => None,
+        (_, None) => Some(format!("'{}' needs a value", op.as_str())),
+        (Op::Lt | Op::Le | Op::Gt | Op::Ge, Some(v)) if as_number(v).is_none() => {
+            Some(format!("'{}' needs a numeric value", op.as_str()))
+        }
+        (Op::Matches, Some(Value::String(pattern))) => Regex::new(pattern).err().map(|e| format!("invalid regex: {e}")),
+        (Op::Matches, Some(_)) => Some("'matches' needs a regex string".into()),
+        _ => None,
+    }
+}
+
+fn compare(actual: Option<&Value>, op: Op, expected: Option<&Value>, regex: Option<&Regex>) -> Result<(), String> {
+    let Some(actual) = actual else {
+        return Err("nothing".into());
+    };
+    let expected = match (op, expected) {
+        (Op::Exists, _) => return Ok(()),
+        (_, Some(expected)) => expected,
+        (_, None) => return Err(format!("{actual} ('{}' needs a value)", op.as_str())),
+    };
+    let holds = match op {
+        Op::Eq => values_equal(actual, expected),
+        Op::Ne => !values_equal(actual, expected),
+        Op::Lt | Op::Le | Op::Gt | Op::Ge => match (as_number(actual), as_number(expected)) {
+            (Some(a), Some(e)) => match op {
+                Op::Lt => a < e,
+                Op::Le => a <= e,
+                Op::Gt => a > e,
+                _ => a >= e,
+            },
+            _ => false,
+        },
+        Op::Contains => match (actual, expected) {
+            (Value::String(a), Value::String(e)) => a.contains(e.as_str()),
+            (Value::Array(items), e) => items.iter().any(|item| values_equal(item, e)),
+            _ => false,
+        },
+        Op::Matches => match actual {
+            Value::String(a) => regex.is_some_and(|re| re.is_match(a)),
+            _ => false,
+        },
+        Op::Exists => true,
+    };
+    if holds { Ok(()) } else { Err(actual.to_string()) }
+}
+
+/// Numbers compare by value, so `200` equals `200.0`.
+fn values_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64() == y.as_f64(),
+        _ => a == b,
+    }
+}
+
+/// Numeric strings count as numbers, so headers such as `Content-Length` can be compared.
+fn as_number(v: &Value) -> Option<f64> {
+    match v {
+        Value::Number(n) => n.as_f64(),
+        Value::String(s) => s.trim().parse().ok(),
+        _ => None,
+    }
+}
+
+/// `Err` holds the path of the first mismatch and the value found there.
+fn subset(expected: &Value, actual: &Value, path: String) -> Result<(), (String, String)> {
+    match (expected, actual) {
+        (Value::Object(expected), Value::Object(actual)) => {
+            for (key, value) in expected {
+                let child = format!("{path}.{key}");
+                match actual.get(key) {
+                    Some(found) => subset(value, found, child)?,
+                    None => return Err((child, "nothing".into())),
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(expected), Value::Array(actual)) if expected.len() == actual.len() => expected
+            .iter()
+            .zip(actual)
+            .enumerate()
+            .try_for_each(|(i, (e, a))| subset(e, a, format!("{path}[{i}]"))),
+        _ if values_equal(expected, actual) => Ok(()),
+        _ => Err((path, actual.to_string())),
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Key(String),
+    Index(usize),
+}
+
+/// The JSONPath subset used by assertions: `$`, `.key`, `['key']` and `[index]`.
+fn parse_path(path: &str) -> Result<Vec<Segment>, String> {
+    let invalid = |why: &str| format!("invalid JSONPath {path:?}: {why}");
+    let mut rest = path.strip_prefix('$').ok_or_else(|| invalid("must start with '$'"))?;
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            if end == 0 {
+                return Err(invalid("empty key"));
+            }
+            segments.push(Segment::Key(after[..end].to_string()));
+            rest = &after[end..];
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(|| invalid("unclosed '['"))?;
+            let inner = after[..end].trim();
+            let quoted = inner
+                .strip_prefix('\'')
+                .and_then(|s| s.strip_suffix('\''))
+                .or_else(|| inner.strip_prefix('"').and_then(|s| s.strip_suffix('"')));
+            match quoted {
+                Some(key) => segments.push(Segment::Key(key.to_string())),
+                None => segments.push(Segment::Index(
+                    inner.parse().map_err(|_| invalid("expected an index or a quoted key in '[]'"))?,
+                )),
+            }
+            rest = &after[end + 1..];
+        } else {
+            return Err(invalid("expected '.' or '['"));
+        }
+    }
+    Ok(segments)
+}
+
+fn select<'a>(value: &'a Value, segments: &[Segment]) -> Option<&'a Value> {
+    segments.iter().try_fold(value, |value, segment| match segment {
+        Segment::Key(key) => value.get(key.as_str()),
+        Segment::Index(i) => value.get(*i),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn assertions(json: Value) -> Vec<ResolvedAssertion> {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn failures(assertions: &[ResolvedAssertion], status: u16, headers: &HeaderMap, body: &str) -> Vec<String> {
+        evaluate(assertions, &Response { status, headers, body }).iter().map(|f| f.to_string()).collect()
+    }
+
+    #[test]
+    fn passing_assertions() {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", "application/json; charset=utf-8".parse().unwrap());
+        headers.insert("content-length", "120".parse().unwrap());
+        let body = r#"{"status":"UP","version":"1.4.2","db":{"latency_ms":12.5},"checks":[{"name":"db"},{"name":"cache"}]}"#;
+        let list = assertions(json!([
+            { "type": "json_subset", "value": { "status": "UP", "db": {} } },
+            { "type": "json_path", "path": "$.db.latency_ms", "op": "lt", "value": 200 },
+            { "type": "json_path", "path": "$.checks[1]['name']", "value": "cache" },
+            { "type": "json_path", "path": "$.version", "op": "matches", "value": "^1\\." },
+            { "type": "json_path", "path": "$.version", "op": "exists" },
+            { "type": "body_contains", "value": "\"UP\"" },
+            { "type": "body_regex", "pattern": "status\"\\s*:\\s*\"UP" },
+            { "type": "header", "name": "Content-Type", "op": "contains", "value": "json" },
+            { "type": "header", "name": "content-length", "op": "le", "value": 1024 },
+            { "type": "status", "in": ["2xx", 301] }
+        ]));
+        assert!(failures(&list, 200, &headers, body).is_empty());
+    }
+
+    #[test]
+    fn failures_name_assertion_and_actual_value() {
+        let body = r#"{"status":"DOWN","db":{"latency_ms":350},"tags":["a"]}"#;
+        let list = assertions(json!([
+            { "type": "json_subset", "value": { "status": "UP" } },
+            { "type": "json_path", "path": "$.db.latency_ms", "op": "lt", "value": 200 },
+            { "type": "json_path", "path": "$.version", "op": "exists" },
+            { "type": "json_path", "path": "$.tags", "op": "contains", "value": "b" },
+            { "type": "body_contains", "value": "healthy" },
+            { "type": "header", "name": "x-served-by", "op": "exists" },
+            { "type": "status", "in": ["200-204"] }
+        ]));
+        assert_eq!(failures(&list, 503, &HeaderMap::new(), body), vec![
+            r#"assertions[0] json subset {"status":"UP"} failed, got "DOWN" at $.status"#,
+            "assertions[1] $.db.latency_ms lt 200 failed, got 350",
+            "assertions[2] $.version exists failed, got nothing",
+            r#"assertions[3] $.tags contains "b" failed, got ["a"]"#,
+            r#"assertions[4] body contains "healthy" failed, got "{\"status\":\"DOWN\",\"db\":{\"latency_ms\":350},\"tags\":[\"a\"]}""#,
+            "assertions[5] header x-served-by exists failed, got nothing",
+            "assertions[6] status in [200-204] failed, got 503",
+        ]);
+    }
+
+    #[test]
+    fn json_assertions_on_non_json_body() {
+        let list = assertions(json!([{ "type": "json_path", "path": "$.status", "value": "UP" }]));
+        assert_eq!(failures(&list, 200, &HeaderMap::new(), "OK"), vec![
+            r#"assertions[0] $.status eq "UP" failed, got non-JSON body "OK""#,
+        ]);
+    }
+
+    #[test]
+    fn parse_path_segments() {
+        assert_eq!(parse_path("$").unwrap(), vec![]);
+        assert_eq!(parse_path("$.a[0]['b c'].d").unwrap(), vec![
+            Segment::Key("a".into()),
+            Segment::Index(0),
+            Segment::Key("b c".into()),
+            Segment::Key("d".into()),
+        ]);
+        assert!(parse_path("a.b").is_err());
+        assert!(parse_path("$..a").is_err());
+        assert!(parse_path("$[x]").is_err());
+        assert!(parse_path("$[0").is_err());
+    }
+
+    #[test]
+    fn check_rejects_bad_assertions() {
+        let list: Vec<Assertion> = serde_json::from_value(json!([
+            { "type": "json_path", "path": "status", "value": "UP" },
+            { "type": "json_path", "path": "$.n", "op": "gt", "value": "many" },
+            { "type": "json_path", "path": "$.n", "op": "eq" },
+            { "type": "body_regex", "pattern": "(" },
+            { "type": "status", "in": ["6xx"] },
+            { "type": "status", "in": [] },
+            { "type": "header", "name": "content-type", "op": "exists" }
+        ]))
+        .unwrap();
+        let problems: Vec<Option<String>> = list.iter().map(Assertion::check).collect();
+        assert_eq!(problems[0].as_deref(), Some("invalid JSONPath \"status\": must start with '$'"));
+        assert_eq!(problems[1].as_deref(), Some("'gt' needs a numeric value"));
+        assert_eq!(problems[2].as_deref(), Some("'eq' needs a value"));
+        assert!(problems[3].as_ref().unwrap().starts_with("invalid regex: "));
+        assert_eq!(problems[4].as_deref(), Some("invalid status pattern \"6xx\", expected e.g. \"2xx\" or \"200-299\""));
+        assert_eq!(problems[5].as_deref(), Some("status assertion needs at least one code"));
+        assert_eq!(problems[6], None);
+    }
+
+    #[test]
+    fn regexes_compile_when_resolved() {
+        let list = assertions(json!([
+            { "type": "body_regex", "pattern": "^ok$" },
+            { "type": "header", "name": "server", "op": "matches", "value": "^nginx" },
+            { "type": "json_path", "path": "$.v", "op": "matches", "value": "(" },
+            { "type": "body_contains", "value": "ok" }
+        ]));
+        let compiled: Vec<Option<&str>> = list.iter().map(|a| a.regex.as_ref().map(Regex::as_str)).collect();
+        assert_eq!(compiled, [Some("^ok$"), Some("^nginx"), None, None]);
+        assert_eq!(serde_json::to_value(&list[0]).unwrap(), json!({ "type": "body_regex", "pattern": "^ok$" }));
+    }
+}
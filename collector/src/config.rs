@@ -1,13 +1,40 @@
-use serde::Deserialize;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
 use std::path::Path;
 use std::time::Duration;
 
+use crate::assertion::{Assertion, ResolvedAssertion};
+use crate::env;
+use crate::maintenance::MaintenanceWindow;
+use crate::secrets;
+use crate::validate::{self, ValidationError};
+
+const DEFAULT_PING_COUNT: u32 = 4;
+const DEFAULT_MAX_PACKET_LOSS_PCT: f32 = 25.0;
+
 #[derive(Deserialize)]
 pub struct Config {
     pub defaults: Defaults,
     #[serde(default = "default_retention_days")]
     pub retention_days: u32,
+    /// Alert targets for projects that don't define their own.
+    #[serde(default)]
+    pub notifiers: Vec<NotifierConfig>,
+    /// Outgoing mail server shared by all `email` notifiers.
+    pub smtp: Option<SmtpConfig>,
+    /// Windows applying to every monitor, in addition to project and monitor windows.
+    #[serde(default)]
+    pub maintenance: Vec<MaintenanceWindow>,
     pub projects: Vec<Project>,
+    /// Templates for the admin API's `/probe`, keyed by module name. The probe's target fills in
+    /// `url` for `http` modules and `host` (and optionally `port`) for the others.
+    #[serde(default)]
+    pub probe_modules: BTreeMap<String, Monitor>,
+    /// Values substituted for `${VAR}` and `secret_file` references.
+    #[serde(skip)]
+    pub secrets: Vec<String>,
 }
 
 fn default_retention_days() -> u32 {
@@ -22,6 +49,33 @@ pub struct Defaults {
     pub expected_status_code: u16,
     #[serde(default = "default_http_method")]
     pub http_method: String,
+    #[serde(default = "default_threshold")]
+    pub failures_before_down: u32,
+    #[serde(default = "default_threshold")]
+    pub successes_before_up: u32,
+    #[serde(default)]
+    pub retry_count: u32,
+    #[serde(default = "default_retry_interval_sec")]
+    pub retry_interval_sec: u64,
+    /// Certificates closer than this to expiry are flagged as a warning.
+    #[serde(default = "default_cert_warn_days")]
+    pub cert_warn_days: u32,
+    /// Certificates closer than this to expiry fail the check; 0 fails only expired ones.
+    #[serde(default)]
+    pub cert_min_days: u32,
+    /// Open a new connection for every `http` check instead of reusing a pooled one.
+    #[serde(default)]
+    pub fresh_connection: bool,
+    /// Successful checks at least this slow mark the monitor degraded.
+    pub response_warn_ms: Option<u32>,
+    /// Checks at least this slow fail outright.
+    pub response_critical_ms: Option<u32>,
+    /// Sent with every `http` check; a monitor's own entries replace these by name.
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    #[serde(default)]
+    pub query: BTreeMap<String, String>,
+    pub body: Option<RequestBody>,
 }
 
 fn default_status_code() -> u16 {
@@ -32,80 +86,677 @@ fn default_http_method() -> String {
     "GET".to_string()
 }
 
+fn default_threshold() -> u32 {
+    1
+}
+
+fn default_retry_interval_sec() -> u64 {
+    10
+}
+
+fn default_cert_warn_days() -> u32 {
+    14
+}
+
+/// A JSON string is sent as-is; any other JSON value is serialized with a JSON content type.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum RequestBody {
+    Raw(String),
+    Json(serde_json::Value),
+}
+
 #[derive(Deserialize)]
 pub struct Project {
     pub id: String,
+    pub notifiers: Option<Vec<NotifierConfig>>,
+    #[serde(default)]
+    pub maintenance: Vec<MaintenanceWindow>,
     pub monitors: Vec<Monitor>,
 }
 
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct NotifierConfig {
+    /// Label used in logs and `alert_deliveries.target`; defaults to the notifier type.
+    pub name: Option<String>,
+    #[serde(default = "default_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_backoff_ms")]
+    pub backoff_ms: u64,
+    #[serde(flatten)]
+    pub kind: NotifierKind,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_backoff_ms() -> u64 {
+    1000
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NotifierKind {
+    Webhook(WebhookConfig),
+    Email(EmailConfig),
+    Slack(SlackConfig),
+    Telegram(TelegramConfig),
+    Discord(DiscordConfig),
+    Matrix(MatrixConfig),
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// JSON payload whose string values may contain `{{placeholders}}`.
+    pub template: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct SlackConfig {
+    pub webhook_url: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    #[serde(deserialize_with = "string_or_integer")]
+    pub chat_id: String,
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_string()
+}
+
+/// Accepts ids given as a JSON number, like Telegram's `-10042`, as well as strings.
+fn string_or_integer<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Id {
+        String(String),
+        Integer(i64),
+    }
+    Ok(match Id::deserialize(deserializer)? {
+        Id::String(s) => s,
+        Id::Integer(n) => n.to_string(),
+    })
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct DiscordConfig {
+    pub webhook_url: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct MatrixConfig {
+    pub homeserver: String,
+    pub access_token: String,
+    pub room_id: String,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct EmailConfig {
+    pub to: Vec<String>,
+    /// Subject and body templates; see `Alert::render` for placeholders.
+    pub subject: Option<String>,
+    pub body: Option<String>,
+    /// Copied from `Config::smtp` during `resolve`.
+    #[serde(skip)]
+    pub smtp: Option<SmtpConfig>,
+}
+
+#[derive(Deserialize, Clone, PartialEq)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub tls: SmtpTls,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpTls {
+    #[default]
+    Starttls,
+    Implicit,
+    None,
+}
+
+impl SmtpConfig {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.tls {
+            SmtpTls::Starttls => 587,
+            SmtpTls::Implicit => 465,
+            SmtpTls::None => 25,
+        })
+    }
+}
+
+impl NotifierConfig {
+    pub fn kind_str(&self) -> &'static str {
+        match self.kind {
+            NotifierKind::Webhook(_) => "webhook",
+            NotifierKind::Email(_) => "email",
+            NotifierKind::Slack(_) => "slack",
+            NotifierKind::Telegram(_) => "telegram",
+            NotifierKind::Discord(_) => "discord",
+            NotifierKind::Matrix(_) => "matrix",
+        }
+    }
+
+    pub fn target(&self) -> &str {
+        self.name.as_deref().unwrap_or(self.kind_str())
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckType {
+    #[default]
+    Http,
+    Tcp,
+    Icmp,
+    Dns,
+    Tls,
+}
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Default, Debug)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum DnsRecordType {
+    #[default]
+    A,
+    Aaaa,
+    Cname,
+    Mx,
+    Txt,
+    Ns,
+}
+
+impl DnsRecordType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DnsRecordType::A => "A",
+            DnsRecordType::Aaaa => "AAAA",
+            DnsRecordType::Cname => "CNAME",
+            DnsRecordType::Mx => "MX",
+            DnsRecordType::Txt => "TXT",
+            DnsRecordType::Ns => "NS",
+        }
+    }
+}
+
 #[derive(Deserialize)]
 pub struct Monitor {
+    /// Required for monitors; unused by probe modules.
+    #[serde(default)]
     pub site_key: String,
-    pub url: String,
+    #[serde(rename = "type", default)]
+    pub check_type: CheckType,
+    /// Target of `http` monitors.
+    pub url: Option<String>,
+    /// Target of `tcp`, `icmp` and `tls` monitors, or the name a `dns` monitor queries.
+    pub host: Option<String>,
+    /// Required for `tcp`; `tls` defaults to 443.
+    pub port: Option<u16>,
+    /// Payload written after a `tcp` connect, e.g. `"PING\r\n"`.
+    pub send: Option<String>,
+    /// Substring the `tcp` banner or reply must contain.
+    pub expect: Option<String>,
+    /// Echo requests sent per `icmp` check (default 4).
+    pub ping_count: Option<u32>,
+    /// Packet loss above which an `icmp` check fails (default 25).
+    pub max_packet_loss_pct: Option<f32>,
+    /// Record type a `dns` monitor asks for (default `A`).
+    pub record_type: Option<DnsRecordType>,
+    /// `ip` or `ip:port` of the server a `dns` monitor queries; the system resolver if unset.
+    pub resolver: Option<String>,
+    /// Records that must all appear in a `dns` answer, e.g. `"10 mx1.example.com"` for MX.
+    pub expected_answers: Option<Vec<String>>,
+    /// Fewest records a `dns` answer may hold (default 1).
+    pub min_answers: Option<u32>,
     pub interval_sec: Option<u64>,
     pub timeout_sec: Option<u64>,
     pub expected_status_code: Option<u16>,
     pub http_method: Option<String>,
+    pub headers: Option<BTreeMap<String, String>>,
+    pub query: Option<BTreeMap<String, String>>,
+    pub body: Option<RequestBody>,
     pub expected_body: Option<serde_json::Value>,
+    /// Checked against `http` responses in addition to `expected_status_code` and `expected_body`;
+    /// a `status` assertion replaces `expected_status_code`.
+    pub assertions: Option<Vec<Assertion>>,
     pub tls_skip_verify: Option<bool>,
+    pub fresh_connection: Option<bool>,
+    pub failures_before_down: Option<u32>,
+    pub successes_before_up: Option<u32>,
+    pub retry_count: Option<u32>,
+    pub retry_interval_sec: Option<u64>,
+    pub cert_warn_days: Option<u32>,
+    pub cert_min_days: Option<u32>,
+    pub response_warn_ms: Option<u32>,
+    pub response_critical_ms: Option<u32>,
+    #[serde(default)]
+    pub maintenance: Vec<MaintenanceWindow>,
+    /// Keeps the monitor and its history but runs no checks.
+    #[serde(default)]
+    pub paused: bool,
 }
 
 pub type MonitorKey = (String, String);
 
-#[derive(PartialEq)]
+#[derive(PartialEq, Clone)]
 pub struct ResolvedMonitor {
     pub project_id: String,
     pub site_key: String,
+    /// What is being checked: the request URL for `http`, `tcp://host:port` for `tcp`, `icmp://host` for `icmp`,
+    /// `dns://resolver/name?type=A` for `dns`, `tls://host:port` for `tls`. Secrets are redacted.
     pub url: String,
     pub interval: Duration,
     pub timeout: Duration,
+    pub check: CheckSpec,
+    pub failures_before_down: u32,
+    pub successes_before_up: u32,
+    pub retry_count: u32,
+    pub retry_interval: Duration,
+    /// Response time from which a successful check counts as slow.
+    pub response_warn_ms: Option<u32>,
+    /// Response time from which a check counts as failed.
+    pub response_critical_ms: Option<u32>,
+    pub notifiers: Vec<NotifierConfig>,
+    pub maintenance: Vec<MaintenanceWindow>,
+    pub secrets: Vec<String>,
+    pub paused: bool,
+}
+
+#[derive(PartialEq, Clone)]
+pub enum CheckSpec {
+    Http(HttpCheck),
+    Tcp(TcpCheck),
+    Icmp(IcmpCheck),
+    Dns(DnsCheck),
+    Tls(TlsCheck),
+}
+
+#[derive(PartialEq, Clone)]
+pub struct HttpCheck {
+    /// Request URL with secrets in place.
+    pub url: String,
     pub expected_status_code: u16,
     pub http_method: String,
+    pub headers: Vec<(String, String)>,
+    pub query: BTreeMap<String, String>,
+    pub body: Option<RequestBody>,
     pub expected_body: Option<serde_json::Value>,
+    pub assertions: Vec<ResolvedAssertion>,
     pub tls_skip_verify: bool,
+    pub fresh_connection: bool,
+    pub cert: CertExpiry,
+}
+
+#[derive(PartialEq, Clone)]
+pub struct TcpCheck {
+    pub host: String,
+    pub port: u16,
+    pub send: Option<String>,
+    pub expect: Option<String>,
+}
+
+#[derive(PartialEq, Clone)]
+pub struct IcmpCheck {
+    pub host: String,
+    pub count: u32,
+    pub max_packet_loss_pct: f32,
+}
+
+#[derive(PartialEq, Clone)]
+pub struct DnsCheck {
+    pub name: String,
+    pub record_type: DnsRecordType,
+    pub resolver: Option<SocketAddr>,
+    pub expected_answers: Vec<String>,
+    pub min_answers: u32,
+}
+
+#[derive(PartialEq, Clone)]
+pub struct TlsCheck {
+    pub host: String,
+    pub port: u16,
+    pub tls_skip_verify: bool,
+    pub cert: CertExpiry,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct CertExpiry {
+    pub warn_days: u32,
+    pub min_days: u32,
+}
+
+impl CheckSpec {
+    pub fn kind_str(&self) -> &'static str {
+        match self {
+            CheckSpec::Http(_) => "http",
+            CheckSpec::Tcp(_) => "tcp",
+            CheckSpec::Icmp(_) => "icmp",
+            CheckSpec::Dns(_) => "dns",
+            CheckSpec::Tls(_) => "tls",
+        }
+    }
+    /// The check's target as a URL, in the form described on `ResolvedMonitor::url`.
+    pub fn target_url(&self) -> String {
+        match self {
+            CheckSpec::Http(http) => http.url.clone(),
+            CheckSpec::Tcp(tcp) => format!("tcp://{}", host_port(&tcp.host, tcp.port)),
+            CheckSpec::Icmp(icmp) => format!("icmp://{}", icmp.host),
+            CheckSpec::Dns(dns) => {
+                let authority = dns.resolver.map(|addr| format!("//{addr}/")).unwrap_or_default();
+                format!("dns:{authority}{}?type={}", dns.name, dns.record_type.as_str())
+            }
+            CheckSpec::Tls(tls) => format!("tls://{}", host_port(&tls.host, tls.port)),
+        }
+    }
 }
 
 impl ResolvedMonitor {
     pub fn key(&self) -> MonitorKey {
         (self.project_id.clone(), self.site_key.clone())
     }
+
+    /// `text` with every config secret masked, for anything logged or stored.
+    pub fn redact(&self, text: &str) -> String {
+        secrets::redact(text, &self.secrets)
+    }
+}
+
+/// Default headers not overridden by the monitor, then the monitor's own. Names compare case-insensitively.
+fn merge_headers(defaults: &BTreeMap<String, String>, overrides: BTreeMap<String, String>) -> Vec<(String, String)> {
+    let mut headers: Vec<(String, String)> = defaults
+        .iter()
+        .filter(|(name, _)| !overrides.keys().any(|o| o.eq_ignore_ascii_case(name)))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect();
+    headers.extend(overrides);
+    headers
+}
+
+/// Parses a `dns` resolver given as `ip` or `ip:port`, defaulting to port 53.
+pub fn parse_resolver(s: &str) -> Option<SocketAddr> {
+    s.parse::<SocketAddr>()
+        .ok()
+        .or_else(|| s.parse::<IpAddr>().ok().map(|ip| SocketAddr::new(ip, 53)))
+}
+
+/// `host:port`, bracketing IPv6 literals.
+pub fn host_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{host}]:{port}")
+    } else {
+        format!("{host}:{port}")
+    }
+}
+
+pub enum ConfigError {
+    Read(std::io::Error),
+    Parse(serde_json::Error),
+    Invalid(Vec<ValidationError>),
+}
+
+impl ConfigError {
+    /// 1-based position of a parse or type error; `None` for other errors and for type errors
+    /// that only show once secrets are resolved.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            ConfigError::Read(_) | ConfigError::Invalid(_) => None,
+            ConfigError::Parse(e) if e.line() == 0 => None,
+            ConfigError::Parse(e) => Some((e.line(), e.column())),
+        }
+    }
+}
+
+/// Type errors are found in the resolved value, which has no positions. The raw text mostly fails
+/// the same way at the same path, which gives the position; otherwise the error names the path.
+fn locate_type_error(contents: &str, e: serde_path_to_error::Error<serde_json::Error>) -> serde_json::Error {
+    let raw = serde_path_to_error::deserialize::<_, Config>(&mut serde_json::Deserializer::from_str(contents));
+    match raw {
+        Err(raw) if raw.path().to_string() == e.path().to_string() => raw.into_inner(),
+        _ => serde::de::Error::custom(format!("{}: {}", e.path(), e.inner())),
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(e) => write!(f, "failed to read config: {e}"),
+            ConfigError::Parse(e) => write!(f, "failed to parse config: {e}"),
+            ConfigError::Invalid(errors) => {
+                write!(f, "invalid config: ")?;
+                for (i, e) in errors.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{e}")?;
+                }
+                Ok(())
+            }
+        }
+    }
 }
 
 impl Config {
-    pub fn load(path: &Path) -> Config {
-        let contents = std::fs::read_to_string(path)
-            .unwrap_or_else(|e| panic!("failed to read config at {}: {e}", path.display()));
-        serde_json::from_str(&contents)
-            .unwrap_or_else(|e| panic!("failed to parse config at {}: {e}", path.display()))
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        let contents = std::fs::read_to_string(path).map_err(ConfigError::Read)?;
+        let mut value: serde_json::Value = serde_json::from_str(&contents).map_err(ConfigError::Parse)?;
+        let dirs = env::lookup_dirs(path.parent());
+        env::load_dotenv(&dirs);
+        let resolved = secrets::resolve(&mut value, &|name| std::env::var(name).ok(), &dirs);
+        if !resolved.errors.is_empty() {
+            return Err(ConfigError::Invalid(resolved.errors));
+        }
+        let mut config: Config = serde_path_to_error::deserialize(value)
+            .map_err(|e| ConfigError::Parse(locate_type_error(&contents, e)))?;
+        config.secrets = resolved.secrets;
+        let errors = validate::validate(&config);
+        if !errors.is_empty() {
+            return Err(ConfigError::Invalid(errors));
+        }
+        Ok(config)
+    }
+
+    fn resolve_notifiers(&self, project_notifiers: Option<&[NotifierConfig]>) -> Vec<NotifierConfig> {
+        let mut notifiers = project_notifiers.unwrap_or(&self.notifiers).to_vec();
+        for notifier in &mut notifiers {
+            if let NotifierKind::Email(email) = &mut notifier.kind {
+                email.smtp = self.smtp.clone();
+            }
+        }
+        notifiers
     }
 
-    pub fn resolve(self) -> Vec<ResolvedMonitor> {
+    pub fn resolve(mut self) -> Vec<ResolvedMonitor> {
         let mut resolved = Vec::new();
-        for project in self.projects {
-            for monitor in project.monitors {
-                resolved.push(ResolvedMonitor {
-                    project_id: project.id.clone(),
-                    site_key: monitor.site_key,
-                    url: monitor.url,
-                    interval: Duration::from_secs(
-                        monitor.interval_sec.unwrap_or(self.defaults.interval_sec),
-                    ),
-                    timeout: Duration::from_secs(
-                        monitor.timeout_sec.unwrap_or(self.defaults.timeout_sec),
-                    ),
-                    expected_status_code: monitor
-                        .expected_status_code
-                        .unwrap_or(self.defaults.expected_status_code),
-                    http_method: monitor
-                        .http_method
-                        .unwrap_or_else(|| self.defaults.http_method.clone()),
-                    expected_body: monitor.expected_body,
-                    tls_skip_verify: monitor.tls_skip_verify.unwrap_or(false),
-                });
+        for mut project in std::mem::take(&mut self.projects) {
+            for monitor in std::mem::take(&mut project.monitors) {
+                resolved.push(self.resolve_monitor(&project, monitor));
             }
         }
         resolved
     }
+
+    /// Probe modules by name, with an empty target that `probe::aim` fills in per request. Unlike monitors,
+    /// they don't inherit `defaults.headers`, `defaults.query` or `defaults.body`.
+    /// `http_2xx`, `tcp_connect`, `icmp`, `dns` and `tls` exist unless the config redefines them.
+    pub fn resolve_probe_modules(&mut self) -> BTreeMap<String, ResolvedMonitor> {
+        let project = Project {
+            id: "probe".into(),
+            notifiers: Some(Vec::new()),
+            maintenance: Vec::new(),
+            monitors: Vec::new(),
+        };
+        let builtin = serde_json::json!({
+            "http_2xx": { "type": "http", "assertions": [{ "type": "status", "in": ["2xx"] }] },
+            "tcp_connect": { "type": "tcp" },
+            "icmp": { "type": "icmp" },
+            "dns": { "type": "dns" },
+            "tls": { "type": "tls" },
+        });
+        let mut modules: BTreeMap<String, Monitor> =
+            serde_json::from_value(builtin).expect("built-in probe modules are valid");
+        modules.extend(std::mem::take(&mut self.probe_modules));
+        modules
+            .into_iter()
+            .map(|(name, mut module)| {
+                module.site_key = name.clone();
+                // Default headers, query and body often carry credentials, and a probe's target is whatever
+                // the caller names, so only the module's own request settings go with it.
+                let (headers, query, body) = (module.headers.clone(), module.query.clone(), module.body.clone());
+                let mut resolved = self.resolve_monitor(&project, module);
+                if let CheckSpec::Http(http) = &mut resolved.check {
+                    http.headers = headers.unwrap_or_default().into_iter().collect();
+                    http.query = query.unwrap_or_default();
+                    http.body = body;
+                }
+                (name, resolved)
+            })
+            .collect()
+    }
+
+    fn resolve_monitor(&self, project: &Project, monitor: Monitor) -> ResolvedMonitor {
+        let cert = CertExpiry {
+            warn_days: monitor.cert_warn_days.unwrap_or(self.defaults.cert_warn_days),
+            min_days: monitor.cert_min_days.unwrap_or(self.defaults.cert_min_days),
+        };
+        let check = match monitor.check_type {
+            CheckType::Http => CheckSpec::Http(HttpCheck {
+                url: monitor.url.unwrap_or_default(),
+                expected_status_code: monitor
+                    .expected_status_code
+                    .unwrap_or(self.defaults.expected_status_code),
+                http_method: monitor
+                    .http_method
+                    .unwrap_or_else(|| self.defaults.http_method.clone()),
+                headers: merge_headers(&self.defaults.headers, monitor.headers.unwrap_or_default()),
+                query: self
+                    .defaults
+                    .query
+                    .clone()
+                    .into_iter()
+                    .chain(monitor.query.unwrap_or_default())
+                    .collect(),
+                body: monitor.body.or_else(|| self.defaults.body.clone()),
+                expected_body: monitor.expected_body,
+                assertions: monitor.assertions.unwrap_or_default().into_iter().map(ResolvedAssertion::from).collect(),
+                tls_skip_verify: monitor.tls_skip_verify.unwrap_or(false),
+                fresh_connection: monitor
+                    .fresh_connection
+                    .unwrap_or(self.defaults.fresh_connection),
+                cert,
+            }),
+            CheckType::Tcp => CheckSpec::Tcp(TcpCheck {
+                host: monitor.host.unwrap_or_default(),
+                port: monitor.port.unwrap_or_default(),
+                send: monitor.send,
+                expect: monitor.expect,
+            }),
+            CheckType::Icmp => CheckSpec::Icmp(IcmpCheck {
+                host: monitor.host.unwrap_or_default(),
+                count: monitor.ping_count.unwrap_or(DEFAULT_PING_COUNT),
+                max_packet_loss_pct: monitor
+                    .max_packet_loss_pct
+                    .unwrap_or(DEFAULT_MAX_PACKET_LOSS_PCT),
+            }),
+            CheckType::Dns => CheckSpec::Dns(DnsCheck {
+                name: monitor.host.unwrap_or_default(),
+                record_type: monitor.record_type.unwrap_or_default(),
+                resolver: monitor.resolver.as_deref().and_then(parse_resolver),
+                expected_answers: monitor.expected_answers.unwrap_or_default(),
+                min_answers: monitor.min_answers.unwrap_or(1),
+            }),
+            CheckType::Tls => CheckSpec::Tls(TlsCheck {
+                host: monitor.host.unwrap_or_default(),
+                port: monitor.port.unwrap_or(443),
+                tls_skip_verify: monitor.tls_skip_verify.unwrap_or(false),
+                cert,
+            }),
+        };
+        ResolvedMonitor {
+            project_id: project.id.clone(),
+            site_key: monitor.site_key,
+            url: secrets::redact(&check.target_url(), &self.secrets),
+            interval: Duration::from_secs(
+                monitor.interval_sec.unwrap_or(self.defaults.interval_sec),
+            ),
+            timeout: Duration::from_secs(
+                monitor.timeout_sec.unwrap_or(self.defaults.timeout_sec),
+            ),
+            check,
+            failures_before_down: monitor
+                .failures_before_down
+                .unwrap_or(self.defaults.failures_before_down),
+            successes_before_up: monitor
+                .successes_before_up
+                .unwrap_or(self.defaults.successes_before_up),
+            retry_count: monitor.retry_count.unwrap_or(self.defaults.retry_count),
+            retry_interval: Duration::from_secs(
+                monitor
+                    .retry_interval_sec
+                    .unwrap_or(self.defaults.retry_interval_sec),
+            ),
+            response_warn_ms: monitor.response_warn_ms.or(self.defaults.response_warn_ms),
+            response_critical_ms: monitor
+                .response_critical_ms
+                .or(self.defaults.response_critical_ms),
+            notifiers: self.resolve_notifiers(project.notifiers.as_deref()),
+            maintenance: self
+                .maintenance
+                .iter()
+                .chain(&project.maintenance)
+                .chain(&monitor.maintenance)
+                .cloned()
+                .collect(),
+            secrets: self.secrets.clone(),
+            paused: monitor.paused,
+        }
+    }
+}
+
+/// Plain GET monitor for tests elsewhere in the crate.
+#[cfg(test)]
+pub fn test_monitor(url: &str) -> ResolvedMonitor {
+    ResolvedMonitor {
+        project_id: "test-proj".into(),
+        site_key: "test-site".into(),
+        url: url.to_string(),
+        interval: Duration::from_secs(60),
+        timeout: Duration::from_secs(5),
+        check: CheckSpec::Http(HttpCheck {
+            url: url.to_string(),
+            expected_status_code: 200,
+            http_method: "GET".into(),
+            headers: Vec::new(),
+            query: BTreeMap::new(),
+            body: None,
+            expected_body: None,
+            assertions: Vec::new(),
+            tls_skip_verify: false,
+            fresh_connection: false,
+            cert: CertExpiry { warn_days: 14, min_days: 0 },
+        }),
+        failures_before_down: 1,
+        successes_before_up: 1,
+        retry_count: 0,
+        retry_interval: Duration::from_secs(10),
+        response_warn_ms: None,
+        response_critical_ms: None,
+        notifiers: Vec::new(),
+        maintenance: Vec::new(),
+        secrets: Vec::new(),
+        paused: false,
+    }
 }
 
 #[cfg(test)]
@@ -129,16 +780,26 @@ mod tests {
         assert_eq!(resolved.len(), 1);
         let m = &resolved[0];
         assert_eq!(m.project_id, "proj1");
+        assert_eq!(m.url, "http://example.com");
         assert_eq!(m.interval, Duration::from_secs(60));
         assert_eq!(m.timeout, Duration::from_secs(10));
-        assert_eq!(m.expected_status_code, 200);
-        assert_eq!(m.http_method, "GET");
+        let CheckSpec::Http(http) = &m.check else { panic!("expected http check") };
+        assert_eq!(http.expected_status_code, 200);
+        assert_eq!(http.http_method, "GET");
+        assert!(!http.fresh_connection);
+        assert_eq!(m.failures_before_down, 1);
+        assert_eq!(m.successes_before_up, 1);
+        assert_eq!(m.retry_count, 0);
+        assert_eq!(m.retry_interval, Duration::from_secs(10));
+        assert_eq!(m.response_warn_ms, None);
+        assert_eq!(m.response_critical_ms, None);
+        assert!(!m.paused);
     }
 
     #[test]
     fn monitor_overrides_take_precedence() {
         let config = parse(r#"{
-            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "defaults": { "interval_sec": 60, "timeout_sec": 10, "response_warn_ms": 2000, "response_critical_ms": 8000 },
             "projects": [{
                 "id": "proj1",
                 "monitors": [{
@@ -147,7 +808,14 @@ mod tests {
                     "interval_sec": 30,
                     "timeout_sec": 5,
                     "expected_status_code": 204,
-                    "http_method": "HEAD"
+                    "http_method": "HEAD",
+                    "fresh_connection": true,
+                    "failures_before_down": 3,
+                    "successes_before_up": 2,
+                    "retry_count": 3,
+                    "retry_interval_sec": 5,
+                    "response_warn_ms": 500,
+                    "paused": true
                 }]
             }]
         }"#);
@@ -155,8 +823,321 @@ mod tests {
         let m = &resolved[0];
         assert_eq!(m.interval, Duration::from_secs(30));
         assert_eq!(m.timeout, Duration::from_secs(5));
-        assert_eq!(m.expected_status_code, 204);
-        assert_eq!(m.http_method, "HEAD");
+        let CheckSpec::Http(http) = &m.check else { panic!("expected http check") };
+        assert_eq!(http.expected_status_code, 204);
+        assert_eq!(http.http_method, "HEAD");
+        assert!(http.fresh_connection);
+        assert_eq!(m.failures_before_down, 3);
+        assert_eq!(m.successes_before_up, 2);
+        assert_eq!(m.retry_count, 3);
+        assert_eq!(m.retry_interval, Duration::from_secs(5));
+        assert_eq!(m.response_warn_ms, Some(500));
+        assert_eq!(m.response_critical_ms, Some(8000));
+        assert!(m.paused);
+    }
+
+    #[test]
+    fn request_fields_merge_over_defaults() {
+        let config = parse(r#"{
+            "defaults": {
+                "interval_sec": 60, "timeout_sec": 10,
+                "headers": { "Authorization": "Bearer default", "User-Agent": "upmon" },
+                "query": { "source": "upmon", "v": "1" },
+                "body": "ping"
+            },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "a", "url": "http://example.com",
+                      "headers": { "authorization": "Bearer site", "Host": "api.internal" },
+                      "query": { "v": "2" },
+                      "body": { "probe": true } },
+                    { "site_key": "b", "url": "http://example.com" }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        let CheckSpec::Http(http) = &resolved[0].check else { panic!("expected http check") };
+        let pairs = |v: &[(&str, &str)]| v.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<Vec<_>>();
+        assert_eq!(http.headers, pairs(&[("User-Agent", "upmon"), ("Host", "api.internal"), ("authorization", "Bearer site")]));
+        assert_eq!(http.query, BTreeMap::from([("source".into(), "upmon".into()), ("v".into(), "2".into())]));
+        assert_eq!(http.body, Some(RequestBody::Json(serde_json::json!({ "probe": true }))));
+        let CheckSpec::Http(http) = &resolved[1].check else { panic!("expected http check") };
+        assert_eq!(http.headers.len(), 2);
+        assert_eq!(http.body, Some(RequestBody::Raw("ping".into())));
+    }
+
+    #[test]
+    fn tcp_monitor_resolves_target() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "smtp", "type": "tcp", "host": "mail.example.com", "port": 25, "expect": "220 " },
+                    { "site_key": "db", "type": "tcp", "host": "::1", "port": 5432 }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        assert_eq!(resolved[0].url, "tcp://mail.example.com:25");
+        let CheckSpec::Tcp(tcp) = &resolved[0].check else { panic!("expected tcp check") };
+        assert_eq!(tcp.host, "mail.example.com");
+        assert_eq!(tcp.port, 25);
+        assert_eq!(tcp.expect.as_deref(), Some("220 "));
+        assert!(tcp.send.is_none());
+        assert_eq!(resolved[1].url, "tcp://[::1]:5432");
+    }
+
+    #[test]
+    fn icmp_monitor_defaults() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "gw", "type": "icmp", "host": "10.0.0.1" },
+                    { "site_key": "edge", "type": "icmp", "host": "edge.example.com", "ping_count": 10, "max_packet_loss_pct": 0 }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        assert_eq!(resolved[0].url, "icmp://10.0.0.1");
+        let CheckSpec::Icmp(icmp) = &resolved[0].check else { panic!("expected icmp check") };
+        assert_eq!(icmp.count, 4);
+        assert_eq!(icmp.max_packet_loss_pct, 25.0);
+        let CheckSpec::Icmp(icmp) = &resolved[1].check else { panic!("expected icmp check") };
+        assert_eq!(icmp.count, 10);
+        assert_eq!(icmp.max_packet_loss_pct, 0.0);
+    }
+
+    #[test]
+    fn dns_monitor_resolves_target() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "apex", "type": "dns", "host": "example.com" },
+                    { "site_key": "mx", "type": "dns", "host": "example.com", "record_type": "MX", "resolver": "1.1.1.1",
+                      "expected_answers": ["10 mx1.example.com"] }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        assert_eq!(resolved[0].url, "dns:example.com?type=A");
+        let CheckSpec::Dns(dns) = &resolved[0].check else { panic!("expected dns check") };
+        assert_eq!(dns.record_type, DnsRecordType::A);
+        assert!(dns.resolver.is_none());
+        assert_eq!(dns.min_answers, 1);
+        assert_eq!(resolved[1].url, "dns://1.1.1.1:53/example.com?type=MX");
+        let CheckSpec::Dns(dns) = &resolved[1].check else { panic!("expected dns check") };
+        assert_eq!(dns.resolver, Some("1.1.1.1:53".parse().unwrap()));
+        assert_eq!(dns.expected_answers, vec!["10 mx1.example.com"]);
+    }
+
+    #[test]
+    fn tls_monitor_and_cert_thresholds() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10, "cert_min_days": 3 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "web", "url": "https://example.com" },
+                    { "site_key": "ldaps", "type": "tls", "host": "ldap.example.com", "port": 636, "cert_warn_days": 30 },
+                    { "site_key": "apex", "type": "tls", "host": "example.com", "cert_min_days": 7 }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        let CheckSpec::Http(http) = &resolved[0].check else { panic!("expected http check") };
+        assert_eq!(http.cert, CertExpiry { warn_days: 14, min_days: 3 });
+        assert_eq!(resolved[1].url, "tls://ldap.example.com:636");
+        let CheckSpec::Tls(tls) = &resolved[1].check else { panic!("expected tls check") };
+        assert_eq!(tls.cert, CertExpiry { warn_days: 30, min_days: 3 });
+        assert!(!tls.tls_skip_verify);
+        assert_eq!(resolved[2].url, "tls://example.com:443");
+        let CheckSpec::Tls(tls) = &resolved[2].check else { panic!("expected tls check") };
+        assert_eq!(tls.cert, CertExpiry { warn_days: 14, min_days: 7 });
+    }
+
+    #[test]
+    fn project_notifiers_override_global() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "notifiers": [{ "type": "webhook", "url": "http://global.example" }],
+            "projects": [
+                {
+                    "id": "proj1",
+                    "notifiers": [{ "type": "webhook", "name": "team", "url": "http://team.example", "max_attempts": 5 }],
+                    "monitors": [{ "site_key": "site1", "url": "http://example.com" }]
+                },
+                {
+                    "id": "proj2",
+                    "monitors": [{ "site_key": "site1", "url": "http://example.com" }]
+                }
+            ]
+        }"#);
+        let resolved = config.resolve();
+        let team = &resolved[0].notifiers[0];
+        assert_eq!(team.target(), "team");
+        assert_eq!(team.max_attempts, 5);
+        assert!(matches!(&team.kind, NotifierKind::Webhook(w) if w.url == "http://team.example"));
+        let global = &resolved[1].notifiers[0];
+        assert_eq!(global.target(), "webhook");
+        assert_eq!(global.backoff_ms, 1000);
+        assert!(matches!(&global.kind, NotifierKind::Webhook(w) if w.url == "http://global.example"));
+    }
+
+    #[test]
+    fn telegram_chat_id_may_be_a_number() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "notifiers": [
+                { "type": "telegram", "bot_token": "123:abc", "chat_id": -10042 },
+                { "type": "telegram", "bot_token": "123:abc", "chat_id": "@ops" }
+            ],
+            "projects": []
+        }"#);
+        let ids: Vec<&str> = config
+            .notifiers
+            .iter()
+            .map(|n| match &n.kind {
+                NotifierKind::Telegram(t) => t.chat_id.as_str(),
+                _ => panic!("expected telegram notifier"),
+            })
+            .collect();
+        assert_eq!(ids, vec!["-10042", "@ops"]);
+    }
+
+    #[test]
+    fn email_notifiers_get_smtp_settings() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "smtp": { "host": "mail.example.com", "tls": "implicit", "from": "upmon@example.com" },
+            "projects": [{
+                "id": "proj1",
+                "notifiers": [{ "type": "email", "to": ["ops@example.com"] }],
+                "monitors": [{ "site_key": "site1", "url": "http://example.com" }]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        let NotifierKind::Email(email) = &resolved[0].notifiers[0].kind else {
+            panic!("expected email notifier");
+        };
+        let smtp = email.smtp.as_ref().unwrap();
+        assert_eq!(smtp.host, "mail.example.com");
+        assert_eq!(smtp.port(), 465);
+        assert_eq!(email.to, vec!["ops@example.com"]);
+    }
+
+    #[test]
+    fn maintenance_windows_accumulate() {
+        let config = parse(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "maintenance": [{ "cron": "0 3 * * *", "duration_min": 30 }],
+            "projects": [{
+                "id": "proj1",
+                "maintenance": [{ "start": "2026-03-01T00:00:00Z", "end": "2026-03-01T01:00:00Z" }],
+                "monitors": [
+                    { "site_key": "site1", "url": "http://example.com",
+                      "maintenance": [{ "cron": "0 12 * * SAT", "duration_min": 60 }] },
+                    { "site_key": "site2", "url": "http://example.com" }
+                ]
+            }]
+        }"#);
+        let resolved = config.resolve();
+        assert_eq!(resolved[0].maintenance.len(), 3);
+        assert_eq!(resolved[1].maintenance.len(), 2);
+        assert!(matches!(
+            &resolved[1].maintenance[0],
+            MaintenanceWindow::Recurring { cron, duration_min: 30 } if cron.as_str() == "0 3 * * *"
+        ));
+    }
+
+    #[test]
+    fn load_reports_parse_position() {
+        let path = std::env::temp_dir().join(format!("upmon-config-{}.json", std::process::id()));
+        std::fs::write(&path, "{\n  \"defaults\": {\n    \"interval_sec\": ,\n").unwrap();
+        let err = Config::load(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, ConfigError::Parse(_)));
+        assert_eq!(err.position(), Some((3, 21)));
+    }
+
+    #[test]
+    fn load_resolves_secrets_and_redacts_url() {
+        // Cargo sets CARGO_PKG_NAME when running tests, so it stands in for a secret.
+        let path = std::env::temp_dir().join(format!("upmon-config-secrets-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [{
+                    "site_key": "api",
+                    "url": "https://example.com/health?key=${CARGO_PKG_NAME}",
+                    "headers": { "Authorization": "Bearer ${CARGO_PKG_NAME}" }
+                }]
+            }]
+        }"#).unwrap();
+        let config = Config::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        let resolved = config.ok().unwrap().resolve();
+        let m = &resolved[0];
+        assert_eq!(m.url, "https://example.com/health?key=<redacted>");
+        let CheckSpec::Http(http) = &m.check else { panic!("expected http check") };
+        assert_eq!(http.url, "https://example.com/health?key=upmon-collector");
+        assert_eq!(http.headers, vec![("Authorization".to_string(), "Bearer upmon-collector".to_string())]);
+        assert_eq!(m.redact("got Bearer upmon-collector"), "got Bearer <redacted>");
+    }
+
+    #[test]
+    fn load_finds_dotenv_and_secret_files_next_to_config() {
+        // The working directory is the crate root, not the config's directory.
+        let dir = std::env::temp_dir().join(format!("upmon-config-dir-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(".env"), "UPMON_TEST_DOTENV_TOKEN=from-dotenv\n").unwrap();
+        std::fs::write(dir.join("smtp-password"), "from-file\n").unwrap();
+        std::fs::write(dir.join("config.json"), r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10, "headers": { "X-Token": "${UPMON_TEST_DOTENV_TOKEN}" } },
+            "smtp": { "host": "mail.example.com", "from": "upmon@example.com", "password": { "secret_file": "smtp-password" } },
+            "projects": []
+        }"#).unwrap();
+        let config = Config::load(&dir.join("config.json"));
+        std::fs::remove_dir_all(&dir).unwrap();
+        let config = config.ok().unwrap();
+        assert_eq!(config.defaults.headers["X-Token"], "from-dotenv");
+        assert_eq!(config.smtp.unwrap().password.as_deref(), Some("from-file"));
+    }
+
+    #[test]
+    fn load_reports_missing_env_var() {
+        let path = std::env::temp_dir().join(format!("upmon-config-missing-{}.json", std::process::id()));
+        std::fs::write(&path, r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "smtp": { "host": "mail.example.com", "from": "upmon@example.com", "password": "${UPMON_TEST_UNSET}" },
+            "projects": []
+        }"#).unwrap();
+        let err = Config::load(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(err.to_string(), "invalid config: smtp.password: environment variable UPMON_TEST_UNSET is not set");
+    }
+
+    #[test]
+    fn load_type_error_reports_position() {
+        let path = std::env::temp_dir().join(format!("upmon-config-type-{}.json", std::process::id()));
+        std::fs::write(&path, "{\n  \"defaults\": { \"interval_sec\": \"60\", \"timeout_sec\": 10 },\n  \"projects\": []\n}").unwrap();
+        let err = Config::load(&path).err().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, ConfigError::Parse(_)));
+        assert_eq!(err.position(), Some((2, 36)));
+    }
+
+    #[test]
+    fn load_missing_file_is_read_error() {
+        let err = Config::load(Path::new("/nonexistent/config.json")).err().unwrap();
+        assert!(matches!(err, ConfigError::Read(_)));
+        assert_eq!(err.position(), None);
     }
 
     #[test]
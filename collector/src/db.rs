@@ -1,7 +1,11 @@
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 
-use crate::models::CheckResult;
+use crate::alert::{Alert, Delivery};
+use crate::config::{MonitorKey, NotifierConfig, ResolvedMonitor};
+use crate::models::{CheckResult, IncidentKind, TlsCertificate};
+use crate::state::MonitorState;
 
 pub async fn init_pool(database_url: &str) -> PgPool {
     PgPoolOptions::new()
@@ -19,9 +23,13 @@ pub async fn run_migrations(pool: &PgPool) {
 }
 
 pub async fn insert_check_result(pool: &PgPool, result: &CheckResult) -> Result<(), sqlx::Error> {
+    let ping = result.ping.as_ref();
+    let timings = result.timings.as_ref();
     sqlx::query(
-        "INSERT INTO monitor_checks (project_id, site_key, url, status_code, response_ms, is_up, error_type, error_message, checked_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        "INSERT INTO monitor_checks (project_id, site_key, url, status_code, response_ms, is_up, error_type, error_message, checked_at,
+                                     is_retry, in_maintenance, packet_loss_pct, rtt_min_ms, rtt_avg_ms, rtt_max_ms, jitter_ms,
+                                     dns_records, dns_ms, connect_ms, tls_ms, ttfb_ms, download_ms, is_degraded)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23)",
     )
     .bind(&result.project_id)
     .bind(&result.site_key)
@@ -32,17 +40,61 @@ pub async fn insert_check_result(pool: &PgPool, result: &CheckResult) -> Result<
     .bind(result.error_type.as_ref().map(|e| e.as_str()))
     .bind(&result.error_message)
     .bind(result.checked_at)
+    .bind(result.is_retry)
+    .bind(result.in_maintenance)
+    .bind(ping.map(|p| p.packet_loss_pct))
+    .bind(ping.and_then(|p| p.rtt_min_ms))
+    .bind(ping.and_then(|p| p.rtt_avg_ms))
+    .bind(ping.and_then(|p| p.rtt_max_ms))
+    .bind(ping.and_then(|p| p.jitter_ms))
+    .bind(&result.dns_records)
+    .bind(timings.and_then(|t| t.dns_ms))
+    .bind(timings.and_then(|t| t.connect_ms))
+    .bind(timings.and_then(|t| t.tls_ms))
+    .bind(timings.and_then(|t| t.ttfb_ms))
+    .bind(timings.and_then(|t| t.download_ms))
+    .bind(result.degraded)
     .execute(pool)
     .await?;
     Ok(())
 }
 
-pub async fn upsert_monitor_status(pool: &PgPool, result: &CheckResult) -> Result<(), sqlx::Error> {
-    let last_up_at = if result.is_up { Some(result.checked_at) } else { None };
+pub async fn fetch_monitor_state(
+    pool: &PgPool,
+    project_id: &str,
+    site_key: &str,
+) -> Result<Option<MonitorState>, sqlx::Error> {
+    let row: Option<(bool, bool, i32, i32, i32, i32)> = sqlx::query_as(
+        "SELECT is_up, is_degraded, consecutive_failures, consecutive_successes, consecutive_slow, consecutive_fast
+         FROM monitor_status WHERE project_id = $1 AND site_key = $2 AND last_checked_at IS NOT NULL",
+    )
+    .bind(project_id)
+    .bind(site_key)
+    .fetch_optional(pool)
+    .await?;
+    Ok(row.map(|(is_up, is_degraded, failures, successes, slow, fast)| MonitorState {
+        is_up,
+        is_degraded,
+        consecutive_failures: failures.max(0) as u32,
+        consecutive_successes: successes.max(0) as u32,
+        consecutive_slow: slow.max(0) as u32,
+        consecutive_fast: fast.max(0) as u32,
+    }))
+}
+
+/// Stores the latest raw check alongside the confirmed state, which may lag behind it.
+pub async fn upsert_monitor_status(
+    pool: &PgPool,
+    result: &CheckResult,
+    state: &MonitorState,
+) -> Result<(), sqlx::Error> {
+    let last_up_at = if state.is_up { Some(result.checked_at) } else { None };
 
     sqlx::query(
-        "INSERT INTO monitor_status (project_id, site_key, url, status_code, response_ms, is_up, error_type, error_message, last_checked_at, last_up_at)
-         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        "INSERT INTO monitor_status (project_id, site_key, url, status_code, response_ms, is_up, error_type, error_message,
+                                     last_checked_at, last_up_at, consecutive_failures, consecutive_successes, in_maintenance,
+                                     is_degraded, consecutive_slow, consecutive_fast)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
          ON CONFLICT (project_id, site_key) DO UPDATE SET
            url = EXCLUDED.url,
            status_code = EXCLUDED.status_code,
@@ -52,18 +104,260 @@ pub async fn upsert_monitor_status(pool: &PgPool, result: &CheckResult) -> Resul
            error_message = EXCLUDED.error_message,
            last_checked_at = EXCLUDED.last_checked_at,
            last_up_at = CASE WHEN EXCLUDED.is_up THEN EXCLUDED.last_checked_at
-                        ELSE monitor_status.last_up_at END",
+                        ELSE monitor_status.last_up_at END,
+           consecutive_failures = EXCLUDED.consecutive_failures,
+           consecutive_successes = EXCLUDED.consecutive_successes,
+           in_maintenance = EXCLUDED.in_maintenance,
+           is_degraded = EXCLUDED.is_degraded,
+           consecutive_slow = EXCLUDED.consecutive_slow,
+           consecutive_fast = EXCLUDED.consecutive_fast,
+           paused = FALSE,
+           paused_at = NULL",
     )
     .bind(&result.project_id)
     .bind(&result.site_key)
     .bind(&result.url)
     .bind(result.status_code)
     .bind(result.response_ms)
-    .bind(result.is_up)
+    .bind(state.is_up)
     .bind(result.error_type.as_ref().map(|e| e.as_str()))
     .bind(&result.error_message)
     .bind(result.checked_at)
     .bind(last_up_at)
+    .bind(state.consecutive_failures.min(i32::MAX as u32) as i32)
+    .bind(state.consecutive_successes.min(i32::MAX as u32) as i32)
+    .bind(result.in_maintenance)
+    .bind(state.is_degraded)
+    .bind(state.consecutive_slow.min(i32::MAX as u32) as i32)
+    .bind(state.consecutive_fast.min(i32::MAX as u32) as i32)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Flags the monitor as paused. A monitor that has never been checked gets a status row
+/// with no check results, so the dashboard can still show it as paused.
+pub async fn mark_monitor_paused(
+    pool: &PgPool,
+    monitor: &ResolvedMonitor,
+    paused_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO monitor_status (project_id, site_key, url, paused, paused_at)
+         VALUES ($1, $2, $3, TRUE, $4)
+         ON CONFLICT (project_id, site_key) DO UPDATE SET
+           paused = TRUE,
+           paused_at = EXCLUDED.paused_at",
+    )
+    .bind(&monitor.project_id)
+    .bind(&monitor.site_key)
+    .bind(&monitor.url)
+    .bind(paused_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn fetch_monitor_pauses(pool: &PgPool) -> Result<Vec<MonitorKey>, sqlx::Error> {
+    sqlx::query_as("SELECT project_id, site_key FROM monitor_pauses").fetch_all(pool).await
+}
+
+pub async fn insert_monitor_pause(pool: &PgPool, key: &MonitorKey, paused_at: DateTime<Utc>) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO monitor_pauses (project_id, site_key, paused_at) VALUES ($1, $2, $3)
+         ON CONFLICT (project_id, site_key) DO NOTHING",
+    )
+    .bind(&key.0)
+    .bind(&key.1)
+    .bind(paused_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_monitor_pause(pool: &PgPool, key: &MonitorKey) -> Result<(), sqlx::Error> {
+    sqlx::query("DELETE FROM monitor_pauses WHERE project_id = $1 AND site_key = $2")
+        .bind(&key.0)
+        .bind(&key.1)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Keeps the most recently seen certificate per monitor.
+pub async fn upsert_tls_certificate(pool: &PgPool, result: &CheckResult, cert: &TlsCertificate) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tls_certificates (project_id, site_key, subject, issuer, not_before, not_after, sans, chain_valid,
+                                       hostname_match, expiry_warning, checked_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+         ON CONFLICT (project_id, site_key) DO UPDATE SET
+           subject = EXCLUDED.subject,
+           issuer = EXCLUDED.issuer,
+           not_before = EXCLUDED.not_before,
+           not_after = EXCLUDED.not_after,
+           sans = EXCLUDED.sans,
+           chain_valid = EXCLUDED.chain_valid,
+           hostname_match = EXCLUDED.hostname_match,
+           expiry_warning = EXCLUDED.expiry_warning,
+           checked_at = EXCLUDED.checked_at",
+    )
+    .bind(&result.project_id)
+    .bind(&result.site_key)
+    .bind(&cert.subject)
+    .bind(&cert.issuer)
+    .bind(cert.not_before)
+    .bind(cert.not_after)
+    .bind(&cert.sans)
+    .bind(cert.chain_valid)
+    .bind(cert.hostname_match)
+    .bind(cert.expiry_warning)
+    .bind(result.checked_at)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn delete_checks_older_than(pool: &PgPool, retention_days: u32) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query(
+        "DELETE FROM monitor_checks WHERE checked_at < NOW() - make_interval(days => $1)",
+    )
+    .bind(retention_days as i32)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected())
+}
+
+pub async fn insert_retention_run(
+    pool: &PgPool,
+    executed_at: DateTime<Utc>,
+    retention_days: u32,
+    deleted_count: Option<i64>,
+    duration_ms: i32,
+    error_message: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO retention_runs (executed_at, retention_days, deleted_count, duration_ms, error_message)
+         VALUES ($1, $2, $3, $4, $5)",
+    )
+    .bind(executed_at)
+    .bind(retention_days as i32)
+    .bind(deleted_count)
+    .bind(duration_ms)
+    .bind(error_message)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn insert_config_reload(
+    pool: &PgPool,
+    reloaded_at: DateTime<Utc>,
+    monitor_count: Option<i32>,
+    error_message: Option<&str>,
+    error_position: Option<(usize, usize)>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO config_reloads (reloaded_at, success, monitor_count, error_message, error_line, error_column)
+         VALUES ($1, $2, $3, $4, $5, $6)",
+    )
+    .bind(reloaded_at)
+    .bind(error_message.is_none())
+    .bind(monitor_count)
+    .bind(error_message)
+    .bind(error_position.map(|(line, _)| line as i32))
+    .bind(error_position.map(|(_, column)| column as i32))
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Opens an incident starting at the first failed (or, for `Degraded`, slow) check of the streak
+/// that confirmed it.
+pub async fn open_incident(
+    pool: &PgPool,
+    kind: IncidentKind,
+    first_failure: &CheckResult,
+    failed_checks: u32,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO incidents (project_id, site_key, started_at, failed_checks, first_error_type, first_error_message, kind)
+         VALUES ($1, $2, $3, $4, $5, $6, $7)
+         ON CONFLICT (project_id, site_key, kind) WHERE ended_at IS NULL DO NOTHING",
+    )
+    .bind(&first_failure.project_id)
+    .bind(&first_failure.site_key)
+    .bind(first_failure.checked_at)
+    .bind(failed_checks.min(i32::MAX as u32) as i32)
+    .bind(first_failure.error_type.as_ref().map(|e| e.as_str()))
+    .bind(&first_failure.error_message)
+    .bind(kind.as_str())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn record_incident_failure(
+    pool: &PgPool,
+    kind: IncidentKind,
+    project_id: &str,
+    site_key: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE incidents SET failed_checks = failed_checks + 1
+         WHERE project_id = $1 AND site_key = $2 AND kind = $3 AND ended_at IS NULL",
+    )
+    .bind(project_id)
+    .bind(site_key)
+    .bind(kind.as_str())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Closes the open incident of `kind`, returning its duration in milliseconds if there was one.
+pub async fn close_incident(
+    pool: &PgPool,
+    kind: IncidentKind,
+    project_id: &str,
+    site_key: &str,
+    ended_at: DateTime<Utc>,
+) -> Result<Option<i64>, sqlx::Error> {
+    sqlx::query_scalar(
+        "UPDATE incidents SET
+           ended_at = $3,
+           duration_ms = (EXTRACT(EPOCH FROM ($3 - started_at)) * 1000)::BIGINT
+         WHERE project_id = $1 AND site_key = $2 AND kind = $4 AND ended_at IS NULL
+         RETURNING duration_ms",
+    )
+    .bind(project_id)
+    .bind(site_key)
+    .bind(ended_at)
+    .bind(kind.as_str())
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn insert_alert_delivery(
+    pool: &PgPool,
+    sent_at: DateTime<Utc>,
+    alert: &Alert,
+    notifier: &NotifierConfig,
+    delivery: &Delivery,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO alert_deliveries (sent_at, project_id, site_key, event, notifier, target, success, attempts, status_code, error_message)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+    )
+    .bind(sent_at)
+    .bind(&alert.project_id)
+    .bind(&alert.site_key)
+    .bind(alert.event.as_str())
+    .bind(notifier.kind_str())
+    .bind(notifier.target())
+    .bind(delivery.success)
+    .bind(delivery.attempts.min(i32::MAX as u32) as i32)
+    .bind(delivery.status_code.map(|c| c as i16))
+    .bind(&delivery.error_message)
     .execute(pool)
     .await?;
     Ok(())
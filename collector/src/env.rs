@@ -1,14 +1,39 @@
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+
 use serde::Deserialize;
 
 #[derive(Deserialize)]
 pub struct Env {
     pub database_url: String,
+    /// Address of the admin API, e.g. `127.0.0.1:9091`; the API is off when unset.
+    pub admin_listen: Option<SocketAddr>,
+    /// Bearer token the admin API requires; must be set along with `admin_listen`.
+    pub admin_token: Option<String>,
 }
 
 impl Env {
     pub fn load() -> Self {
-        dotenvy::from_filename(".env.local").ok();
-        dotenvy::from_filename(".env").ok();
+        load_dotenv(&lookup_dirs(None));
         envy::from_env().expect("failed to parse environment variables")
     }
 }
+
+/// Where `.env` files and relative `secret_file` paths are looked up, in order: the working directory,
+/// the config's directory and the binary's. A deploy validates a temporary copy of the config from
+/// some other directory, and only the binary's directory finds the files then.
+pub fn lookup_dirs(config_dir: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(".")];
+    dirs.extend(config_dir.filter(|dir| !dir.as_os_str().is_empty()).map(Path::to_path_buf));
+    dirs.extend(std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)));
+    dirs
+}
+
+/// `.env.local`, then `.env`, from each of `dirs`; the first value found for a variable wins,
+/// and variables already set in the environment win over all of them.
+pub fn load_dotenv(dirs: &[PathBuf]) {
+    for dir in dirs {
+        dotenvy::from_path(dir.join(".env.local")).ok();
+        dotenvy::from_path(dir.join(".env")).ok();
+    }
+}
@@ -1,29 +1,91 @@
+mod admin;
+mod alert;
+mod assertion;
 mod config;
 mod db;
+mod maintenance;
+mod metrics;
 mod env;
 mod models;
 mod monitor;
+mod probe;
+mod retention;
 mod scheduler;
+mod secrets;
+mod state;
+mod trigger;
+mod validate;
 
 use std::path::Path;
+use std::process::ExitCode;
+use std::sync::Arc;
 use std::time::Duration;
 
+use tokio::net::TcpListener;
+use tokio::sync::watch;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    match args.get(1).map(String::as_str) {
+        None => {
+            run();
+            ExitCode::SUCCESS
+        }
+        Some("validate") => validate_config(Path::new(
+            args.get(2).map(String::as_str).unwrap_or("config.json"),
+        )),
+        Some(other) => {
+            eprintln!("unknown command '{other}'");
+            eprintln!("usage: upmon-collector [validate [<path>]]");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn validate_config(path: &Path) -> ExitCode {
+    match config::Config::load(path) {
+        Ok(config) => {
+            let count = config.resolve().len();
+            println!("{}: ok ({count} monitors)", path.display());
+            ExitCode::SUCCESS
+        }
+        Err(config::ConfigError::Invalid(errors)) => {
+            for e in &errors {
+                eprintln!("{}: {e}", path.display());
+            }
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("{}: {e}", path.display());
+            ExitCode::FAILURE
+        }
+    }
+}
+
 #[tokio::main]
-async fn main() {
+async fn run() {
     tracing_subscriber::fmt()
         .with_env_filter(EnvFilter::try_from_default_env().unwrap_or_else(|_| "info".into()))
         .init();
 
     let env = env::Env::load();
+    let admin_token = env.admin_listen.map(|_| {
+        env.admin_token
+            .clone()
+            .filter(|token| !token.is_empty())
+            .expect("ADMIN_TOKEN must be set when ADMIN_LISTEN is")
+    });
 
     let config_path = Path::new("config.json");
 
-    let config = config::Config::load(config_path);
-    info!(retention_days = config.retention_days, "config loaded");
+    let mut config = config::Config::load(config_path)
+        .unwrap_or_else(|e| panic!("{e} ({})", config_path.display()));
+    let retention_days = config.retention_days;
+    info!(retention_days, "config loaded");
 
+    let probe_modules = config.resolve_probe_modules();
     let monitors = config.resolve();
     info!(count = monitors.len(), "monitors resolved");
 
@@ -31,10 +93,22 @@ async fn main() {
     db::run_migrations(&pool).await;
     info!("database ready");
 
-    let client = monitor::build_client(Duration::from_secs(30));
-    let insecure_client = monitor::build_insecure_client(Duration::from_secs(30));
+    let clients = monitor::HttpClients::new(Duration::from_secs(30));
 
-    let manager = scheduler::MonitorManager::new(pool, client, insecure_client);
+    let (retention_tx, retention_rx) = watch::channel(retention_days);
+    tokio::spawn(retention::run_prune_loop(pool.clone(), retention_rx));
+
+    let manager = Arc::new(scheduler::MonitorManager::new(pool.clone(), clients, retention_tx));
+    manager.restore_pauses().await;
+    manager.set_probe_modules(probe_modules);
     manager.start_initial(monitors);
+    tokio::spawn(trigger::listen(pool, manager.clone()));
+
+    if let (Some(addr), Some(token)) = (env.admin_listen, admin_token) {
+        let listener = TcpListener::bind(addr).await.expect("failed to bind admin API listener");
+        info!(%addr, "admin API listening");
+        tokio::spawn(admin::serve(listener, token, manager.clone()));
+    }
+
     manager.watch_config(config_path).await;
 }
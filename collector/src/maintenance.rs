@@ -0,0 +1,239 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+
+/// A period during which checks still run but their failures are ignored.
+/// Recurring windows use a five-field cron expression evaluated in UTC.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Debug)]
+#[serde(untagged)]
+pub enum MaintenanceWindow {
+    Once { start: DateTime<Utc>, end: DateTime<Utc> },
+    Recurring { cron: Cron, duration_min: u32 },
+}
+
+/// A cron expression, parsed once when the config is read. Compares and serializes as its text;
+/// an invalid expression is kept so validation can report it, and never matches.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(from = "String", into = "String")]
+pub struct Cron {
+    expr: String,
+    schedule: Result<CronSchedule, String>,
+}
+
+impl Cron {
+    pub fn as_str(&self) -> &str {
+        &self.expr
+    }
+
+    pub fn schedule(&self) -> Result<&CronSchedule, &str> {
+        self.schedule.as_ref().map_err(String::as_str)
+    }
+}
+
+impl From<String> for Cron {
+    fn from(expr: String) -> Self {
+        let schedule = CronSchedule::parse(&expr);
+        Self { expr, schedule }
+    }
+}
+
+impl From<Cron> for String {
+    fn from(cron: Cron) -> Self {
+        cron.expr
+    }
+}
+
+impl PartialEq for Cron {
+    fn eq(&self, other: &Self) -> bool {
+        self.expr == other.expr
+    }
+}
+
+impl MaintenanceWindow {
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        match self {
+            MaintenanceWindow::Once { start, end } => *start <= at && at < *end,
+            MaintenanceWindow::Recurring { cron, duration_min } => {
+                let Ok(schedule) = cron.schedule() else {
+                    return false;
+                };
+                let minute = at.duration_trunc(Duration::minutes(1)).unwrap_or(at);
+                (0..*duration_min as i64).any(|m| schedule.matches(minute - Duration::minutes(m)))
+            }
+        }
+    }
+}
+
+pub fn is_active(windows: &[MaintenanceWindow], at: DateTime<Utc>) -> bool {
+    windows.iter().any(|w| w.contains(at))
+}
+
+#[derive(Clone, Debug)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    dom_restricted: bool,
+    dow_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+impl CronSchedule {
+    /// Parses `minute hour day-of-month month day-of-week`, supporting `*`, lists,
+    /// ranges, steps and three-letter month/day names.
+    pub fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        let [minute, hour, dom, month, dow] = fields[..] else {
+            return Err(format!("expected 5 fields, got {}", fields.len()));
+        };
+        let mut days_of_week = parse_field(dow, 0, 7, &DAY_NAMES, 0)?;
+        // Both 0 and 7 mean Sunday.
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week |= 1;
+        }
+        Ok(Self {
+            minutes: parse_field(minute, 0, 59, &[], 0)?,
+            hours: parse_field(hour, 0, 23, &[], 0)?,
+            days_of_month: parse_field(dom, 1, 31, &[], 0)?,
+            months: parse_field(month, 1, 12, &MONTH_NAMES, 1)?,
+            days_of_week,
+            // As in Vixie cron, a field starting with `*` (like `*/2`) doesn't restrict the day.
+            dom_restricted: !dom.starts_with('*'),
+            dow_restricted: !dow.starts_with('*'),
+        })
+    }
+
+    pub fn matches(&self, t: DateTime<Utc>) -> bool {
+        let bit = |mask: u64, v: u32| mask & (1 << v) != 0;
+        let dom_ok = bit(self.days_of_month, t.day());
+        let dow_ok = bit(self.days_of_week, t.weekday().num_days_from_sunday());
+        // Standard cron: when both day fields are restricted, either may match.
+        let day_ok = if self.dom_restricted && self.dow_restricted {
+            dom_ok || dow_ok
+        } else {
+            dom_ok && dow_ok
+        };
+        bit(self.minutes, t.minute()) && bit(self.hours, t.hour()) && bit(self.months, t.month()) && day_ok
+    }
+}
+
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str], name_base: u32) -> Result<u64, String> {
+    let value = |s: &str| -> Result<u32, String> {
+        if let Some(i) = names.iter().position(|n| n.eq_ignore_ascii_case(s)) {
+            return Ok(i as u32 + name_base);
+        }
+        let v: u32 = s.parse().map_err(|_| format!("invalid value '{s}'"))?;
+        if v < min || v > max {
+            return Err(format!("value {v} out of range {min}-{max}"));
+        }
+        Ok(v)
+    };
+
+    let mut mask = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((r, s)) => {
+                let step: u32 = s.parse().map_err(|_| format!("invalid step '{s}'"))?;
+                if step == 0 {
+                    return Err("step must be greater than 0".into());
+                }
+                (r, step)
+            }
+            None => (part, 1),
+        };
+        let (lo, hi) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (value(a)?, value(b)?)
+        } else {
+            let v = value(range)?;
+            (v, if step > 1 { max } else { v })
+        };
+        if lo > hi {
+            return Err(format!("invalid range '{range}'"));
+        }
+        for v in (lo..=hi).step_by(step as usize) {
+            mask |= 1 << v;
+        }
+    }
+    Ok(mask)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn one_off_window_is_half_open() {
+        let w = MaintenanceWindow::Once {
+            start: at("2026-03-01T02:00:00Z"),
+            end: at("2026-03-01T03:00:00Z"),
+        };
+        assert!(!w.contains(at("2026-03-01T01:59:59Z")));
+        assert!(w.contains(at("2026-03-01T02:00:00Z")));
+        assert!(w.contains(at("2026-03-01T02:59:59Z")));
+        assert!(!w.contains(at("2026-03-01T03:00:00Z")));
+    }
+
+    #[test]
+    fn recurring_window_covers_duration_after_each_start() {
+        // Sundays at 02:30 for 90 minutes; 2026-03-01 is a Sunday.
+        let w = MaintenanceWindow::Recurring { cron: String::from("30 2 * * SUN").into(), duration_min: 90 };
+        assert!(!w.contains(at("2026-03-01T02:29:59Z")));
+        assert!(w.contains(at("2026-03-01T02:30:00Z")));
+        assert!(w.contains(at("2026-03-01T03:59:30Z")));
+        assert!(!w.contains(at("2026-03-01T04:00:00Z")));
+        assert!(!w.contains(at("2026-03-02T02:45:00Z")));
+    }
+
+    #[test]
+    fn cron_steps_lists_and_ranges() {
+        let s = CronSchedule::parse("*/15 9-17 * * 1-5").unwrap();
+        assert!(s.matches(at("2026-03-02T09:45:00Z")));
+        assert!(!s.matches(at("2026-03-02T09:50:00Z")));
+        assert!(!s.matches(at("2026-03-01T09:45:00Z")));
+
+        let s = CronSchedule::parse("0 0,12 1 jan,jul *").unwrap();
+        assert!(s.matches(at("2026-07-01T12:00:00Z")));
+        assert!(!s.matches(at("2026-08-01T12:00:00Z")));
+    }
+
+    #[test]
+    fn cron_restricted_dom_and_dow_match_either() {
+        let s = CronSchedule::parse("0 0 13 * 5").unwrap();
+        assert!(s.matches(at("2026-03-13T00:00:00Z")));
+        assert!(s.matches(at("2026-03-06T00:00:00Z")));
+        assert!(!s.matches(at("2026-03-07T00:00:00Z")));
+    }
+
+    #[test]
+    fn cron_star_step_does_not_restrict_the_day() {
+        // Odd days that are also Sundays; 2026-03-01 is a Sunday.
+        let s = CronSchedule::parse("0 3 */2 * SUN").unwrap();
+        assert!(s.matches(at("2026-03-01T03:00:00Z")));
+        assert!(!s.matches(at("2026-03-03T03:00:00Z")));
+        assert!(!s.matches(at("2026-03-08T03:00:00Z")));
+    }
+
+    #[test]
+    fn cron_seven_is_sunday() {
+        let s = CronSchedule::parse("0 0 * * 7").unwrap();
+        assert!(s.matches(at("2026-03-01T00:00:00Z")));
+    }
+
+    #[test]
+    fn cron_rejects_bad_expressions() {
+        assert_eq!(CronSchedule::parse("* * *").err().unwrap(), "expected 5 fields, got 3");
+        assert_eq!(CronSchedule::parse("60 * * * *").err().unwrap(), "value 60 out of range 0-59");
+        assert_eq!(CronSchedule::parse("*/0 * * * *").err().unwrap(), "step must be greater than 0");
+        assert_eq!(CronSchedule::parse("5-1 * * * *").err().unwrap(), "invalid range '5-1'");
+        assert_eq!(CronSchedule::parse("* * * FOO *").err().unwrap(), "invalid value 'FOO'");
+    }
+}
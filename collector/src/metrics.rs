@@ -0,0 +1,260 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::config::MonitorKey;
+use crate::models::CheckResult;
+use crate::state::MonitorState;
+
+/// Upper bounds of the `upmon_response_ms` buckets.
+const RESPONSE_MS_BUCKETS: [f64; 11] = [10.0, 25.0, 50.0, 100.0, 250.0, 500.0, 1000.0, 2500.0, 5000.0, 10000.0, 30000.0];
+
+/// Counters and gauges rendered in the Prometheus text format by the admin API's `/metrics`.
+pub struct Metrics {
+    started_at: DateTime<Utc>,
+    monitors: Mutex<BTreeMap<MonitorKey, MonitorMetrics>>,
+    db_insert_failures: Mutex<BTreeMap<&'static str, u64>>,
+    config_reloads: AtomicU64,
+    config_reload_failures: AtomicU64,
+}
+
+#[derive(Default)]
+struct MonitorMetrics {
+    up: bool,
+    degraded: bool,
+    paused: bool,
+    response_ms: Histogram,
+    checks: BTreeMap<&'static str, u64>,
+    last_check: Option<DateTime<Utc>>,
+    cert_not_after: Option<DateTime<Utc>>,
+    lag: Option<Duration>,
+}
+
+#[derive(Default)]
+struct Histogram {
+    /// Per bucket, not cumulative; the last slot counts observations above every bound.
+    counts: [u64; RESPONSE_MS_BUCKETS.len() + 1],
+    sum: f64,
+}
+
+impl Histogram {
+    fn observe(&mut self, value: f64) {
+        let bucket = RESPONSE_MS_BUCKETS.iter().position(|&le| value <= le).unwrap_or(RESPONSE_MS_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += value;
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self {
+            started_at: Utc::now(),
+            monitors: Mutex::default(),
+            db_insert_failures: Mutex::default(),
+            config_reloads: AtomicU64::new(0),
+            config_reload_failures: AtomicU64::new(0),
+        }
+    }
+}
+
+impl Metrics {
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    /// Records a stored check along with the confirmed state it led to.
+    pub fn record_check(&self, result: &CheckResult, state: &MonitorState) {
+        let mut monitors = self.monitors.lock().unwrap();
+        let m = monitors.entry((result.project_id.clone(), result.site_key.clone())).or_default();
+        m.up = state.is_up;
+        m.degraded = state.is_degraded;
+        m.paused = false;
+        m.response_ms.observe(result.response_ms.max(0) as f64);
+        *m.checks.entry(result.error_type.as_ref().map_or("none", |e| e.as_str())).or_default() += 1;
+        m.last_check = Some(result.checked_at);
+        if let Some(cert) = &result.tls {
+            m.cert_not_after = Some(cert.not_after);
+        }
+    }
+
+    /// How late a scheduled check started.
+    pub fn record_lag(&self, key: &MonitorKey, lag: Duration) {
+        self.monitors.lock().unwrap().entry(key.clone()).or_default().lag = Some(lag);
+    }
+
+    pub fn record_paused(&self, key: &MonitorKey) {
+        self.monitors.lock().unwrap().entry(key.clone()).or_default().paused = true;
+    }
+
+    /// Drops a removed monitor's series.
+    pub fn remove(&self, key: &MonitorKey) {
+        self.monitors.lock().unwrap().remove(key);
+    }
+
+    pub fn record_db_insert_failure(&self, table: &'static str) {
+        *self.db_insert_failures.lock().unwrap().entry(table).or_default() += 1;
+    }
+
+    pub fn record_config_reload(&self, success: bool) {
+        self.config_reloads.fetch_add(1, Ordering::Relaxed);
+        if !success {
+            self.config_reload_failures.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let monitors = self.monitors.lock().unwrap();
+        let labels = |(project_id, site_key): &MonitorKey| {
+            format!("project_id=\"{}\",site_key=\"{}\"", escape(project_id), escape(site_key))
+        };
+        let gauge = |out: &mut String, name: &str, help: &str, value: &dyn Fn(&MonitorMetrics) -> Option<f64>| {
+            header(out, name, help, "gauge");
+            for (key, m) in monitors.iter() {
+                if let Some(v) = value(m) {
+                    let _ = writeln!(out, "{name}{{{}}} {v}", labels(key));
+                }
+            }
+        };
+        let flag = |b: bool| Some(if b { 1.0 } else { 0.0 });
+
+        gauge(&mut out, "upmon_up", "Confirmed state of the monitor: 1 up, 0 down.", &|m| {
+            m.last_check.and(flag(m.up))
+        });
+        gauge(&mut out, "upmon_degraded", "1 while the monitor is up but slower than response_warn_ms.", &|m| {
+            m.last_check.and(flag(m.degraded))
+        });
+        gauge(&mut out, "upmon_paused", "1 while the monitor is paused.", &|m| flag(m.paused));
+        gauge(&mut out, "upmon_last_check_timestamp", "Unix time of the most recent check.", &|m| {
+            m.last_check.map(seconds)
+        });
+        gauge(&mut out, "upmon_cert_expiry_timestamp", "Unix time the served certificate expires.", &|m| {
+            m.cert_not_after.map(seconds)
+        });
+        gauge(&mut out, "upmon_cert_expiry_days", "Days until the served certificate expires.", &|m| {
+            m.cert_not_after.map(|at| (seconds(at) - seconds(Utc::now())) / 86400.0)
+        });
+        gauge(&mut out, "upmon_scheduler_lag_seconds", "How late the most recent scheduled check started.", &|m| {
+            m.lag.map(|lag| lag.as_secs_f64())
+        });
+
+        header(&mut out, "upmon_response_ms", "Response time of checks in milliseconds.", "histogram");
+        for (key, m) in monitors.iter() {
+            let labels = labels(key);
+            let mut cumulative = 0;
+            for (i, count) in m.response_ms.counts.iter().enumerate() {
+                cumulative += count;
+                let le = RESPONSE_MS_BUCKETS.get(i).map_or("+Inf".to_string(), |le| le.to_string());
+                let _ = writeln!(out, "upmon_response_ms_bucket{{{labels},le=\"{le}\"}} {cumulative}");
+            }
+            let _ = writeln!(out, "upmon_response_ms_sum{{{labels}}} {}", m.response_ms.sum);
+            let _ = writeln!(out, "upmon_response_ms_count{{{labels}}} {cumulative}");
+        }
+
+        header(&mut out, "upmon_checks_total", "Checks run, by error_type (\"none\" for passing checks).", "counter");
+        for (key, m) in monitors.iter() {
+            for (error_type, count) in &m.checks {
+                let _ = writeln!(out, "upmon_checks_total{{{},error_type=\"{error_type}\"}} {count}", labels(key));
+            }
+        }
+
+        header(&mut out, "upmon_db_insert_failures_total", "Failed writes of check results, by table.", "counter");
+        for (table, count) in self.db_insert_failures.lock().unwrap().iter() {
+            let _ = writeln!(out, "upmon_db_insert_failures_total{{table=\"{table}\"}} {count}");
+        }
+        header(&mut out, "upmon_config_reloads_total", "Reloads of config.json, failed ones included.", "counter");
+        let _ = writeln!(out, "upmon_config_reloads_total {}", self.config_reloads.load(Ordering::Relaxed));
+        header(&mut out, "upmon_config_reload_failures_total", "Reloads that kept the previous config.", "counter");
+        let _ = writeln!(out, "upmon_config_reload_failures_total {}", self.config_reload_failures.load(Ordering::Relaxed));
+        header(&mut out, "upmon_monitors", "Monitors with at least one recorded sample.", "gauge");
+        let _ = writeln!(out, "upmon_monitors {}", monitors.len());
+        header(&mut out, "upmon_start_timestamp", "Unix time the collector started.", "gauge");
+        let _ = writeln!(out, "upmon_start_timestamp {}", seconds(self.started_at));
+        out
+    }
+}
+
+pub fn header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
+
+pub fn seconds(at: DateTime<Utc>) -> f64 {
+    at.timestamp_millis() as f64 / 1000.0
+}
+
+/// Label values may not contain raw backslashes, quotes or newlines.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ErrorType;
+
+    fn result(response_ms: i32, error_type: Option<ErrorType>) -> CheckResult {
+        CheckResult {
+            project_id: "proj".into(),
+            site_key: "a\"b".into(),
+            url: "https://example.com".into(),
+            status_code: Some(200),
+            response_ms,
+            is_up: error_type.is_none(),
+            error_type,
+            error_message: None,
+            checked_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+            is_retry: false,
+            in_maintenance: false,
+            ping: None,
+            dns_records: None,
+            tls: None,
+            timings: None,
+            degraded: false,
+        }
+    }
+
+    #[test]
+    fn renders_monitor_series() {
+        let metrics = Metrics::default();
+        let state = MonitorState::default();
+        metrics.record_check(&result(40, None), &state);
+        metrics.record_check(&result(700, None), &state);
+        metrics.record_check(&result(40_000, Some(ErrorType::Timeout)), &state);
+        metrics.record_db_insert_failure("monitor_checks");
+        metrics.record_config_reload(false);
+        let text = metrics.render();
+
+        let labels = r#"project_id="proj",site_key="a\"b""#;
+        for line in [
+            format!("upmon_up{{{labels}}} 1"),
+            format!("upmon_last_check_timestamp{{{labels}}} 1700000000"),
+            format!("upmon_response_ms_bucket{{{labels},le=\"50\"}} 1"),
+            format!("upmon_response_ms_bucket{{{labels},le=\"1000\"}} 2"),
+            format!("upmon_response_ms_bucket{{{labels},le=\"30000\"}} 2"),
+            format!("upmon_response_ms_bucket{{{labels},le=\"+Inf\"}} 3"),
+            format!("upmon_response_ms_sum{{{labels}}} 40740"),
+            format!("upmon_response_ms_count{{{labels}}} 3"),
+            format!("upmon_checks_total{{{labels},error_type=\"none\"}} 2"),
+            format!("upmon_checks_total{{{labels},error_type=\"timeout\"}} 1"),
+            "upmon_db_insert_failures_total{table=\"monitor_checks\"} 1".to_string(),
+            "upmon_config_reloads_total 1".to_string(),
+            "upmon_config_reload_failures_total 1".to_string(),
+        ] {
+            assert!(text.lines().any(|l| l == line), "missing {line} in\n{text}");
+        }
+        assert!(!text.contains("upmon_cert_expiry_timestamp{"));
+    }
+
+    #[test]
+    fn removed_monitors_disappear() {
+        let metrics = Metrics::default();
+        metrics.record_check(&result(40, None), &MonitorState::default());
+        metrics.remove(&("proj".into(), "a\"b".into()));
+        assert!(!metrics.render().contains("upmon_up{"));
+    }
+}
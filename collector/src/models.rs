@@ -1,4 +1,6 @@
 use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::error::Error;
 
 #[derive(Clone)]
 pub enum ErrorType {
@@ -6,6 +8,20 @@ pub enum ErrorType {
     ConnectionError,
     UnexpectedStatus,
     UnexpectedBody,
+    ConnectionRefused,
+    HostUnreachable,
+    BannerMismatch,
+    PacketLoss,
+    DnsFailure,
+    AnswerMismatch,
+    TlsError,
+    CertExpiring,
+    CertExpired,
+    ConnectionReset,
+    TooManyRedirects,
+    BodyReadError,
+    AssertionFailed,
+    SlowResponse,
 }
 
 impl ErrorType {
@@ -15,6 +31,36 @@ impl ErrorType {
             ErrorType::ConnectionError => "connection_error",
             ErrorType::UnexpectedStatus => "unexpected_status",
             ErrorType::UnexpectedBody => "unexpected_body",
+            ErrorType::ConnectionRefused => "connection_refused",
+            ErrorType::HostUnreachable => "host_unreachable",
+            ErrorType::BannerMismatch => "banner_mismatch",
+            ErrorType::PacketLoss => "packet_loss",
+            ErrorType::DnsFailure => "dns_failure",
+            ErrorType::AnswerMismatch => "answer_mismatch",
+            ErrorType::TlsError => "tls_error",
+            ErrorType::CertExpiring => "cert_expiring",
+            ErrorType::CertExpired => "cert_expired",
+            ErrorType::ConnectionReset => "connection_reset",
+            ErrorType::TooManyRedirects => "too_many_redirects",
+            ErrorType::BodyReadError => "body_read_error",
+            ErrorType::AssertionFailed => "assertion_failed",
+            ErrorType::SlowResponse => "slow_response",
+        }
+    }
+}
+
+/// What an incident records: the monitor being down, or up but slow.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IncidentKind {
+    Down,
+    Degraded,
+}
+
+impl IncidentKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            IncidentKind::Down => "down",
+            IncidentKind::Degraded => "degraded",
         }
     }
 }
@@ -30,6 +76,71 @@ pub struct CheckResult {
     pub error_type: Option<ErrorType>,
     pub error_message: Option<String>,
     pub checked_at: DateTime<Utc>,
+    pub is_retry: bool,
+    pub in_maintenance: bool,
+    pub ping: Option<PingStats>,
+    /// Records returned to a `dns` check, in answer order.
+    pub dns_records: Option<Vec<String>>,
+    pub tls: Option<TlsCertificate>,
+    pub timings: Option<HttpTimings>,
+    /// Succeeded, but no faster than the monitor's `response_warn_ms` or with a certificate
+    /// inside `cert_warn_days`.
+    pub degraded: bool,
+}
+
+/// Where the time of an `http` check went. Connection phases are `None` when a pooled
+/// connection was reused or the check failed before reaching them.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct HttpTimings {
+    pub dns_ms: Option<i32>,
+    pub connect_ms: Option<i32>,
+    pub tls_ms: Option<i32>,
+    /// From the connection being ready to the response headers arriving.
+    pub ttfb_ms: Option<i32>,
+    pub download_ms: Option<i32>,
+}
+
+/// Leaf certificate presented by the server of an `https` or `tls` check.
+#[derive(Clone, Debug, Serialize)]
+pub struct TlsCertificate {
+    pub subject: String,
+    pub issuer: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    pub sans: Vec<String>,
+    /// Whether the chain leads to a trusted root; `None` when verification was skipped.
+    pub chain_valid: Option<bool>,
+    pub hostname_match: bool,
+    /// Set when fewer than `cert_warn_days` remain.
+    pub expiry_warning: bool,
+}
+
+/// Round-trip summary of an `icmp` check; RTTs are `None` when nothing came back.
+#[derive(Clone, Debug, Serialize)]
+pub struct PingStats {
+    pub sent: u32,
+    pub received: u32,
+    pub packet_loss_pct: f32,
+    pub rtt_min_ms: Option<f32>,
+    pub rtt_avg_ms: Option<f32>,
+    pub rtt_max_ms: Option<f32>,
+    /// Mean absolute difference between consecutive RTTs.
+    pub jitter_ms: Option<f32>,
+}
+
+/// `e` followed by each of its causes, skipping any a parent already printed.
+pub fn error_chain(e: &dyn Error) -> String {
+    let mut message = e.to_string();
+    let mut source = e.source();
+    while let Some(cause) = source {
+        let text = cause.to_string();
+        if !message.ends_with(&text) {
+            message.push_str(": ");
+            message.push_str(&text);
+        }
+        source = cause.source();
+    }
+    message
 }
 
 const MAX_ERROR_CHARS: usize = 500;
@@ -46,6 +157,29 @@ pub fn truncate_error_message(body: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::fmt;
+
+    #[test]
+    fn error_chain_appends_unprinted_causes() {
+        let inner = std::io::Error::new(std::io::ErrorKind::ConnectionRefused, "connection refused");
+        let outer = std::io::Error::other(ChainLink("tcp connect error", inner));
+        assert_eq!(error_chain(&outer), "tcp connect error: connection refused");
+    }
+
+    #[derive(Debug)]
+    struct ChainLink(&'static str, std::io::Error);
+
+    impl fmt::Display for ChainLink {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(self.0)
+        }
+    }
+
+    impl Error for ChainLink {
+        fn source(&self) -> Option<&(dyn Error + 'static)> {
+            Some(&self.1)
+        }
+    }
 
     #[test]
     fn truncate_under_limit_is_noop() {
@@ -1,34 +1,125 @@
 use chrono::Utc;
-use reqwest::{Client, Method};
-use std::time::Duration;
-use tracing::warn;
+use hyper::Method;
+use reqwest::Client;
+use std::error::Error;
+use std::io;
+use std::time::{Duration, Instant};
+use tokio::time;
+use tracing::{debug, warn};
 
-use crate::config::ResolvedMonitor;
-use crate::models::{CheckResult, ErrorType, truncate_error_message};
+use crate::assertion::{self, ResolvedAssertion};
+use crate::config::{CheckSpec, HttpCheck, ResolvedMonitor};
+use crate::models::{CheckResult, ErrorType, HttpTimings, error_chain, truncate_error_message};
 
-pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckResult {
-    let method = monitor.http_method.parse::<Method>().unwrap_or_else(|_| {
-        panic!("invalid HTTP method '{}' for {}/{}", monitor.http_method, monitor.project_id, monitor.site_key)
+mod client;
+mod dns;
+mod icmp;
+mod tcp;
+mod timing;
+mod tls;
+
+pub use client::CheckClient;
+use client::{CheckRequest, RequestError};
+
+pub async fn execute_check(client: &CheckClient, monitor: &ResolvedMonitor) -> CheckResult {
+    let mut result = match &monitor.check {
+        CheckSpec::Http(http) => execute_http(client, monitor, http).await,
+        CheckSpec::Tcp(tcp) => tcp::execute(monitor, tcp).await,
+        CheckSpec::Icmp(icmp) => icmp::execute(monitor, icmp).await,
+        CheckSpec::Dns(dns) => dns::execute(monitor, dns).await,
+        CheckSpec::Tls(tls) => tls::execute(monitor, tls).await,
+    };
+    apply_response_thresholds(&mut result, monitor);
+    apply_cert_warning(&mut result);
+    result.error_message = result.error_message.map(|message| monitor.redact(&message));
+    result
+}
+
+/// Fails a successful check at `response_critical_ms` and marks it degraded at `response_warn_ms`.
+fn apply_response_thresholds(result: &mut CheckResult, monitor: &ResolvedMonitor) {
+    if !result.is_up {
+        return;
+    }
+    let response_ms = result.response_ms.max(0) as u32;
+    if let Some(critical_ms) = monitor.response_critical_ms
+        && response_ms >= critical_ms
+    {
+        result.is_up = false;
+        result.error_type = Some(ErrorType::SlowResponse);
+        result.error_message = Some(format!("response took {response_ms} ms (critical at {critical_ms} ms)"));
+    } else if let Some(warn_ms) = monitor.response_warn_ms
+        && response_ms >= warn_ms
+    {
+        result.degraded = true;
+        result.error_type = Some(ErrorType::SlowResponse);
+        result.error_message = Some(format!("response took {response_ms} ms (warn at {warn_ms} ms)"));
+    }
+}
+
+/// Marks a successful check degraded when its certificate is inside `cert_warn_days`,
+/// unless it's already degraded for being slow.
+fn apply_cert_warning(result: &mut CheckResult) {
+    if !result.is_up || result.degraded {
+        return;
+    }
+    if let Some(cert) = &result.tls
+        && cert.expiry_warning
+    {
+        result.degraded = true;
+        result.error_type = Some(ErrorType::CertExpiring);
+        result.error_message = Some(format!(
+            "certificate expires in {} days at {}",
+            (cert.not_after - result.checked_at).num_days(),
+            cert.not_after.to_rfc3339()
+        ));
+    }
+}
+
+async fn execute_http(client: &CheckClient, monitor: &ResolvedMonitor, http: &HttpCheck) -> CheckResult {
+    let method = http.http_method.parse::<Method>().unwrap_or_else(|_| {
+        panic!("invalid HTTP method '{}' for {}/{}", http.http_method, monitor.project_id, monitor.site_key)
     });
 
-    let start = std::time::Instant::now();
-    let result = client
-        .request(method, &monitor.url)
-        .timeout(monitor.timeout)
-        .send()
-        .await;
+    if !http.headers.is_empty() {
+        debug!(
+            project = monitor.project_id,
+            site = monitor.site_key,
+            headers = redacted_headers(&http.headers),
+            "sending custom headers"
+        );
+    }
+
+    // The timeout covers the whole exchange, reading the body included.
+    let deadline = time::Instant::now() + monitor.timeout;
+    let start = Instant::now();
+    let (result, clock) = timing::measure(async {
+        let request = CheckRequest::new(http, method)?;
+        time::timeout_at(deadline, client.send(request)).await.unwrap_or(Err(RequestError::Timeout))
+    })
+    .await;
     let elapsed = start.elapsed();
-    let response_ms = elapsed.as_millis().min(i32::MAX as u128) as i32;
+    let response_ms = timing::millis(elapsed);
     let checked_at = Utc::now();
+    let mut timings = clock.timings(elapsed);
 
     match result {
         Ok(response) => {
-            let status = response.status().as_u16();
-            let status_ok = status == monitor.expected_status_code;
+            let status = response.status.as_u16();
+            let status_ok = status == http.expected_status_code || http.assertions.iter().any(ResolvedAssertion::is_status);
+            let headers = response.headers.clone();
+            let mut tls = response.peer_certificate.as_deref().and_then(|der| {
+                let host = response.url.host_str().unwrap_or_default();
+                // A verifying client only gets this far with a trusted chain.
+                tls::inspect(der, host, (!http.tls_skip_verify).then_some(true)).ok()
+            });
 
-            let body_text = match response.text().await {
+            let download_start = Instant::now();
+            let body = time::timeout_at(deadline, response.text()).await.unwrap_or(Err(RequestError::Timeout));
+            timings.download_ms = Some(timing::millis(download_start.elapsed()));
+            let body_text = match body {
                 Ok(t) => t,
                 Err(e) => {
+                    let message = monitor.redact(&format!("failed to read response body: {}", error_chain(&e)));
                     return CheckResult {
                         project_id: monitor.project_id.clone(),
                         site_key: monitor.site_key.clone(),
@@ -36,9 +127,16 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                         status_code: Some(status as i16),
                         response_ms,
                         is_up: false,
-                        error_type: Some(ErrorType::ConnectionError),
-                        error_message: Some(format!("failed to read response body: {e}")),
+                        error_type: Some(classify(&e)),
+                        error_message: Some(truncate_error_message(&message)),
                         checked_at,
+                        is_retry: false,
+                        in_maintenance: false,
+                        ping: None,
+                        dns_records: None,
+                        tls,
+                        timings: Some(timings),
+                        degraded: false,
                     };
                 }
             };
@@ -47,12 +145,12 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                 warn!(
                     project = monitor.project_id,
                     site = monitor.site_key,
-                    expected = monitor.expected_status_code,
+                    expected = http.expected_status_code,
                     actual = status,
                     "unexpected status code"
                 );
-                (false, Some(ErrorType::UnexpectedStatus), Some(truncate_error_message(&body_text)))
-            } else if let Some(expected) = &monitor.expected_body {
+                (false, Some(ErrorType::UnexpectedStatus), Some(truncate_error_message(&monitor.redact(&body_text))))
+            } else if let Some(expected) = &http.expected_body {
                 match serde_json::from_str::<serde_json::Value>(&body_text) {
                     Ok(actual) if &actual == expected => (true, None, None),
                     Ok(actual) => {
@@ -61,7 +159,7 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                             site = monitor.site_key,
                             "unexpected response body"
                         );
-                        (false, Some(ErrorType::UnexpectedBody), Some(truncate_error_message(&actual.to_string())))
+                        (false, Some(ErrorType::UnexpectedBody), Some(truncate_error_message(&monitor.redact(&actual.to_string()))))
                     }
                     Err(_) => {
                         warn!(
@@ -69,12 +167,38 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                             site = monitor.site_key,
                             "response body is not valid JSON"
                         );
-                        (false, Some(ErrorType::UnexpectedBody), Some(truncate_error_message(&body_text)))
+                        (false, Some(ErrorType::UnexpectedBody), Some(truncate_error_message(&monitor.redact(&body_text))))
                     }
                 }
             } else {
                 (true, None, None)
             };
+            let failures = if is_up {
+                assertion::evaluate(&http.assertions, &assertion::Response { status, headers: &headers, body: &body_text })
+            } else {
+                Vec::new()
+            };
+            let (is_up, error_type, error_message) = if failures.is_empty() {
+                (is_up, error_type, error_message)
+            } else {
+                warn!(
+                    project = monitor.project_id,
+                    site = monitor.site_key,
+                    failed = failures.len(),
+                    "response assertions failed"
+                );
+                let error_type = if failures.iter().any(|f| http.assertions[f.index].is_status()) {
+                    ErrorType::UnexpectedStatus
+                } else {
+                    ErrorType::AssertionFailed
+                };
+                let message = failures.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ");
+                (false, Some(error_type), Some(truncate_error_message(&monitor.redact(&message))))
+            };
+            let (is_up, error_type, error_message) = match tls.as_mut().and_then(|cert| tls::check_expiry(monitor, cert, http.cert)) {
+                Some((error_type, message)) if is_up => (false, Some(error_type), Some(message)),
+                _ => (is_up, error_type, error_message),
+            };
 
             CheckResult {
                 project_id: monitor.project_id.clone(),
@@ -86,19 +210,23 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                 error_type,
                 error_message,
                 checked_at,
+                is_retry: false,
+                in_maintenance: false,
+                ping: None,
+                dns_records: None,
+                tls,
+                timings: Some(timings),
+                degraded: false,
             }
         }
         Err(e) => {
-            let error_type = if e.is_timeout() {
-                ErrorType::Timeout
-            } else {
-                ErrorType::ConnectionError
-            };
+            let error_type = classify(&e);
+            let message = truncate_error_message(&monitor.redact(&error_chain(&e)));
             warn!(
                 project = monitor.project_id,
                 site = monitor.site_key,
                 error_type = error_type.as_str(),
-                error = %e,
+                error = message,
                 "check failed"
             );
             CheckResult {
@@ -109,51 +237,114 @@ pub async fn execute_check(client: &Client, monitor: &ResolvedMonitor) -> CheckR
                 response_ms,
                 is_up: false,
                 error_type: Some(error_type),
-                error_message: Some(e.to_string()),
+                error_message: Some(message),
                 checked_at,
+                is_retry: false,
+                in_maintenance: false,
+                ping: None,
+                dns_records: None,
+                tls: None,
+                timings: Some(HttpTimings { ttfb_ms: None, ..timings }),
+                degraded: false,
+            }
+        }
+    }
+}
+
+/// Header names only: values are often credentials.
+fn redacted_headers(headers: &[(String, String)]) -> String {
+    headers
+        .iter()
+        .map(|(name, _)| format!("{name}: <redacted>"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Picks the most specific error type by walking the source chain down to the io or TLS error.
+fn classify(e: &RequestError) -> ErrorType {
+    match e {
+        RequestError::Timeout => return ErrorType::Timeout,
+        RequestError::TooManyRedirects(_) => return ErrorType::TooManyRedirects,
+        _ => {}
+    }
+    let mut source: Option<&(dyn Error + 'static)> = Some(e);
+    while let Some(err) = source {
+        if err.is::<timing::DnsError>() {
+            return ErrorType::DnsFailure;
+        }
+        if err.is::<rustls::Error>() {
+            return ErrorType::TlsError;
+        }
+        if let Some(io) = err.downcast_ref::<io::Error>() {
+            match tcp::classify(io) {
+                ErrorType::ConnectionError => {}
+                error_type => return error_type,
             }
+            // `io::Error::source` skips the wrapped error itself, so step into it directly.
+            source = io.get_ref().map(|inner| inner as &(dyn Error + 'static));
+        } else {
+            source = err.source();
         }
     }
+    if matches!(e, RequestError::Body(_)) {
+        ErrorType::BodyReadError
+    } else {
+        ErrorType::ConnectionError
+    }
 }
 
-pub fn build_client(timeout: Duration) -> Client {
-    Client::builder()
-        .timeout(timeout)
-        .build()
-        .expect("failed to build HTTP client")
+/// A client for everything but checks, and one check client per combination of certificate
+/// verification and connection reuse.
+#[derive(Clone)]
+pub struct HttpClients {
+    shared: Client,
+    pooled: CheckClient,
+    insecure: CheckClient,
+    fresh: CheckClient,
+    fresh_insecure: CheckClient,
 }
 
-pub fn build_insecure_client(timeout: Duration) -> Client {
-    Client::builder()
-        .timeout(timeout)
-        .danger_accept_invalid_certs(true)
-        .build()
-        .expect("failed to build insecure HTTP client")
+impl HttpClients {
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            shared: Client::builder().timeout(timeout).build().expect("failed to build HTTP client"),
+            pooled: CheckClient::new(false, false),
+            insecure: CheckClient::new(true, false),
+            fresh: CheckClient::new(false, true),
+            fresh_insecure: CheckClient::new(true, true),
+        }
+    }
+
+    /// Verifying client with connection pooling, for alerts.
+    pub fn shared(&self) -> &Client {
+        &self.shared
+    }
+
+    pub fn for_check(&self, check: &CheckSpec) -> &CheckClient {
+        match check {
+            CheckSpec::Http(http) => match (http.tls_skip_verify, http.fresh_connection) {
+                (false, false) => &self.pooled,
+                (true, false) => &self.insecure,
+                (false, true) => &self.fresh,
+                (true, true) => &self.fresh_insecure,
+            },
+            _ => &self.pooled,
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wiremock::matchers::{method, path};
+    use crate::config::{RequestBody, test_monitor};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use wiremock::matchers::{body_json, header, method, path, query_param};
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    fn make_monitor(url: &str) -> ResolvedMonitor {
-        ResolvedMonitor {
-            project_id: "test-proj".into(),
-            site_key: "test-site".into(),
-            url: url.to_string(),
-            interval: Duration::from_secs(60),
-            timeout: Duration::from_secs(5),
-            expected_status_code: 200,
-            http_method: "GET".into(),
-            expected_body: None,
-            tls_skip_verify: false,
-        }
-    }
-
     #[test]
-    fn build_insecure_client_succeeds() {
-        let _client = build_insecure_client(Duration::from_secs(10));
+    fn build_clients_succeeds() {
+        let _clients = HttpClients::new(Duration::from_secs(10));
     }
 
     #[tokio::test]
@@ -165,8 +356,8 @@ mod tests {
             .mount(&server)
             .await;
 
-        let client = Client::new();
-        let monitor = make_monitor(&format!("{}/health", server.uri()));
+        let client = CheckClient::default();
+        let monitor = test_monitor(&format!("{}/health", server.uri()));
         let result = execute_check(&client, &monitor).await;
 
         assert!(result.is_up);
@@ -175,6 +366,129 @@ mod tests {
         assert!(result.error_message.is_none());
     }
 
+    #[tokio::test]
+    async fn slow_response_degrades_then_fails() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/slow"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(200)))
+            .mount(&server)
+            .await;
+
+        let client = CheckClient::default();
+        let mut monitor = test_monitor(&format!("{}/slow", server.uri()));
+        monitor.response_warn_ms = Some(100);
+        let result = execute_check(&client, &monitor).await;
+        assert!(result.is_up);
+        assert!(result.degraded);
+        assert_eq!(result.error_type.unwrap().as_str(), "slow_response");
+        assert!(result.error_message.unwrap().ends_with("(warn at 100 ms)"));
+
+        monitor.response_critical_ms = Some(150);
+        let result = execute_check(&client, &monitor).await;
+        assert!(!result.is_up);
+        assert!(!result.degraded);
+        assert!(result.error_message.unwrap().ends_with("(critical at 150 ms)"));
+    }
+
+    #[tokio::test]
+    async fn pooled_connection_skips_setup_phases() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        let clients = HttpClients::new(Duration::from_secs(5));
+        let mut monitor = test_monitor(&format!("http://localhost:{}/", server.address().port()));
+
+        let first = execute_check(clients.for_check(&monitor.check), &monitor).await.timings.unwrap();
+        assert!(first.dns_ms.is_some());
+        assert!(first.connect_ms.is_some());
+        assert!(first.tls_ms.is_none());
+        assert!(first.ttfb_ms.is_some());
+        assert!(first.download_ms.is_some());
+        let reused = execute_check(clients.for_check(&monitor.check), &monitor).await.timings.unwrap();
+        assert!(reused.dns_ms.is_none());
+        assert!(reused.connect_ms.is_none());
+
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.fresh_connection = true;
+        execute_check(clients.for_check(&monitor.check), &monitor).await;
+        let fresh = execute_check(clients.for_check(&monitor.check), &monitor).await.timings.unwrap();
+        assert!(fresh.dns_ms.is_some());
+        assert!(fresh.connect_ms.is_some());
+    }
+
+    #[tokio::test]
+    async fn sends_configured_headers_query_and_body() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rpc"))
+            .and(header("authorization", "Bearer t0ken"))
+            .and(query_param("probe", "1"))
+            .and(body_json(serde_json::json!({ "method": "ping" })))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut monitor = test_monitor(&format!("{}/rpc", server.uri()));
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.http_method = "POST".into();
+        http.headers = vec![("Authorization".into(), "Bearer t0ken".into())];
+        http.query.insert("probe".into(), "1".into());
+        http.body = Some(RequestBody::Json(serde_json::json!({ "method": "ping" })));
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn secrets_are_redacted_from_error_message() {
+        let mut monitor = test_monitor("http://127.0.0.1:1/health?token=s3cr3t");
+        monitor.url = "http://127.0.0.1:1/health?token=<redacted>".into();
+        monitor.secrets = vec!["s3cr3t".into()];
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+
+        let message = result.error_message.unwrap();
+        assert!(message.contains("token=<redacted>"), "{message}");
+        assert!(!message.contains("s3cr3t"));
+    }
+
+    #[tokio::test]
+    async fn assertions_allow_extra_fields_and_report_failures() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(201)
+                    .set_body_json(serde_json::json!({ "status": "UP", "version": "1.2.0", "queue": { "depth": 42 } })),
+            )
+            .mount(&server)
+            .await;
+        let mut monitor = test_monitor(&server.uri());
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.assertions = serde_json::from_value(serde_json::json!([
+            { "type": "status", "in": ["2xx"] },
+            { "type": "json_subset", "value": { "status": "UP" } }
+        ]))
+        .unwrap();
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.assertions
+            .push(serde_json::from_value(serde_json::json!({ "type": "json_path", "path": "$.queue.depth", "op": "lt", "value": 10 })).unwrap());
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "assertion_failed");
+        assert_eq!(result.error_message.unwrap(), "assertions[2] $.queue.depth lt 10 failed, got 42");
+    }
+
+    #[test]
+    fn header_values_are_redacted() {
+        let headers = vec![("Authorization".into(), "Bearer t0ken".into()), ("Host".into(), "api.internal".into())];
+        assert_eq!(redacted_headers(&headers), "Authorization: <redacted>, Host: <redacted>");
+    }
+
     #[tokio::test]
     async fn check_404_unexpected_status() {
         let server = MockServer::start().await;
@@ -184,8 +498,8 @@ mod tests {
             .mount(&server)
             .await;
 
-        let client = Client::new();
-        let monitor = make_monitor(&format!("{}/missing", server.uri()));
+        let client = CheckClient::default();
+        let monitor = test_monitor(&format!("{}/missing", server.uri()));
         let result = execute_check(&client, &monitor).await;
 
         assert!(!result.is_up);
@@ -196,12 +510,100 @@ mod tests {
 
     #[tokio::test]
     async fn check_connection_refused() {
-        let client = Client::new();
-        let monitor = make_monitor("http://127.0.0.1:1");
+        let client = CheckClient::default();
+        let monitor = test_monitor("http://127.0.0.1:1");
         let result = execute_check(&client, &monitor).await;
 
         assert!(!result.is_up);
         assert!(result.status_code.is_none());
-        assert_eq!(result.error_type.as_ref().unwrap().as_str(), "connection_error");
+        assert_eq!(result.error_type.as_ref().unwrap().as_str(), "connection_refused");
+        assert!(result.error_message.unwrap().contains("Connection refused"));
+    }
+
+    #[tokio::test]
+    async fn check_dns_failure() {
+        let monitor = test_monitor("http://does-not-exist.invalid/");
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+
+        assert_eq!(result.error_type.unwrap().as_str(), "dns_failure");
+        assert!(result.error_message.unwrap().contains("dns error"));
+    }
+
+    #[tokio::test]
+    async fn check_connection_reset() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.read(&mut [0u8; 1024]).await;
+            // Zero linger turns the close into a RST.
+            socket2::SockRef::from(&stream).set_linger(Some(Duration::ZERO)).unwrap();
+        });
+        let result = execute_check(&CheckClient::default(), &test_monitor(&format!("http://{addr}/"))).await;
+
+        assert_eq!(result.error_type.unwrap().as_str(), "connection_reset");
+    }
+
+    #[tokio::test]
+    async fn check_tls_error() {
+        let server = MockServer::start().await;
+        let url = server.uri().replace("http://", "https://");
+        let result = execute_check(&CheckClient::default(), &test_monitor(&url)).await;
+
+        assert_eq!(result.error_type.unwrap().as_str(), "tls_error");
+    }
+
+    #[tokio::test]
+    async fn post_follows_see_other_as_get() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/submit"))
+            .respond_with(ResponseTemplate::new(303).insert_header("location", "/done"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/done"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let mut monitor = test_monitor(&format!("{}/submit", server.uri()));
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.http_method = "POST".into();
+        http.body = Some(RequestBody::Raw("payload".into()));
+        let result = execute_check(&CheckClient::default(), &monitor).await;
+
+        assert!(result.is_up, "{:?}", result.error_message);
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[1].body.is_empty());
+    }
+
+    #[tokio::test]
+    async fn check_too_many_redirects() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/loop"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/loop"))
+            .mount(&server)
+            .await;
+        let result = execute_check(&CheckClient::default(), &test_monitor(&format!("{}/loop", server.uri()))).await;
+
+        assert_eq!(result.error_type.unwrap().as_str(), "too_many_redirects");
+    }
+
+    #[tokio::test]
+    async fn check_truncated_body() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let _ = stream.read(&mut [0u8; 1024]).await;
+            stream.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 100\r\n\r\npartial").await.unwrap();
+        });
+        let result = execute_check(&CheckClient::default(), &test_monitor(&format!("http://{addr}/"))).await;
+
+        assert_eq!(result.status_code, Some(200));
+        assert_eq!(result.error_type.unwrap().as_str(), "body_read_error");
+        assert!(result.error_message.unwrap().starts_with("failed to read response body: "));
     }
 }
@@ -0,0 +1,200 @@
+use std::error::Error;
+use std::fmt;
+
+use bytes::Bytes;
+use http_body_util::{BodyExt, Full};
+use hyper::body::Incoming;
+use hyper::header::{
+    ACCEPT, AUTHORIZATION, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, COOKIE, HeaderMap, HeaderName,
+    HeaderValue, LOCATION, PROXY_AUTHORIZATION, TRANSFER_ENCODING, WWW_AUTHENTICATE,
+};
+use hyper::{Method, Request, StatusCode};
+use hyper_util::client::legacy::{self, Client};
+use hyper_util::rt::TokioExecutor;
+use url::Url;
+
+use super::timing::{PeerCertificate, TimedConnector};
+use crate::config::{HttpCheck, RequestBody};
+
+/// Redirects followed before a check fails.
+const MAX_REDIRECTS: usize = 10;
+
+/// HTTP/1.1 client for `http` checks. Its connector times DNS, connect and TLS separately.
+#[derive(Clone)]
+pub struct CheckClient(Client<TimedConnector, Full<Bytes>>);
+
+impl CheckClient {
+    /// `skip_verify` accepts any certificate. A `fresh` client keeps no idle connections or TLS sessions,
+    /// so every check starts from a new DNS lookup, TCP connect and full handshake.
+    pub fn new(skip_verify: bool, fresh: bool) -> Self {
+        let mut builder = Client::builder(TokioExecutor::new());
+        if fresh {
+            builder.pool_max_idle_per_host(0);
+        }
+        Self(builder.build(TimedConnector::new(skip_verify, fresh)))
+    }
+
+    /// Sends `request`, following redirects the way browsers do.
+    pub(super) async fn send(&self, mut request: CheckRequest) -> Result<CheckResponse, RequestError> {
+        for _ in 0..=MAX_REDIRECTS {
+            let response = self
+                .0
+                .request(request.build()?)
+                .await
+                .map_err(|e| RequestError::Send(request.url.clone(), Box::new(e)))?;
+            let next = match response.status() {
+                StatusCode::MOVED_PERMANENTLY
+                | StatusCode::FOUND
+                | StatusCode::SEE_OTHER
+                | StatusCode::TEMPORARY_REDIRECT
+                | StatusCode::PERMANENT_REDIRECT => response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .and_then(|location| request.url.join(location).ok()),
+                _ => None,
+            };
+            match next {
+                Some(next) => request.redirect(response.status(), next),
+                None => {
+                    let (parts, body) = response.into_parts();
+                    return Ok(CheckResponse {
+                        status: parts.status,
+                        peer_certificate: parts.extensions.get::<PeerCertificate>().map(|cert| cert.0.clone()),
+                        headers: parts.headers,
+                        url: request.url,
+                        body,
+                    });
+                }
+            }
+        }
+        Err(RequestError::TooManyRedirects(request.url))
+    }
+}
+
+impl Default for CheckClient {
+    fn default() -> Self {
+        Self::new(false, false)
+    }
+}
+
+pub(super) struct CheckRequest {
+    method: Method,
+    url: Url,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl CheckRequest {
+    /// The request `http` describes: its URL with `query` appended, headers and body.
+    pub(super) fn new(http: &HttpCheck, method: Method) -> Result<Self, RequestError> {
+        let mut url = Url::parse(&http.url).map_err(|e| RequestError::Invalid(format!("invalid URL: {e}")))?;
+        url.set_fragment(None);
+        if !http.query.is_empty() {
+            url.query_pairs_mut().extend_pairs(&http.query);
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert(ACCEPT, HeaderValue::from_static("*/*"));
+        for (name, value) in &http.headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|_| RequestError::Invalid(format!("invalid header name {name:?}")))?;
+            let value =
+                HeaderValue::from_str(value).map_err(|_| RequestError::Invalid(format!("invalid value for header {name}")))?;
+            headers.append(name, value);
+        }
+
+        let body = match &http.body {
+            Some(RequestBody::Raw(body)) => Bytes::from(body.clone()),
+            Some(RequestBody::Json(body)) => {
+                headers.entry(CONTENT_TYPE).or_insert(HeaderValue::from_static("application/json"));
+                Bytes::from(body.to_string())
+            }
+            None => Bytes::new(),
+        };
+        Ok(Self { method, url, headers, body })
+    }
+
+    fn build(&self) -> Result<Request<Full<Bytes>>, RequestError> {
+        let mut request = Request::builder()
+            .method(self.method.clone())
+            .uri(self.url.as_str())
+            .body(Full::new(self.body.clone()))
+            .map_err(|e| RequestError::Invalid(e.to_string()))?;
+        *request.headers_mut() = self.headers.clone();
+        Ok(request)
+    }
+
+    /// Moves the request to `next`. 303, and 301 and 302 after a POST, turn it into a GET without a body;
+    /// credentials stay behind when the host or port changes.
+    fn redirect(&mut self, status: StatusCode, next: Url) {
+        let to_get = match status {
+            StatusCode::SEE_OTHER => true,
+            StatusCode::MOVED_PERMANENTLY | StatusCode::FOUND => self.method == Method::POST,
+            _ => false,
+        };
+        if to_get {
+            if self.method != Method::HEAD {
+                self.method = Method::GET;
+            }
+            self.body = Bytes::new();
+            for header in [CONTENT_TYPE, CONTENT_LENGTH, CONTENT_ENCODING, TRANSFER_ENCODING] {
+                self.headers.remove(header);
+            }
+        }
+        if next.host_str() != self.url.host_str() || next.port_or_known_default() != self.url.port_or_known_default() {
+            for header in [AUTHORIZATION, COOKIE, PROXY_AUTHORIZATION, WWW_AUTHENTICATE] {
+                self.headers.remove(header);
+            }
+        }
+        self.url = next;
+    }
+}
+
+pub(super) struct CheckResponse {
+    pub(super) status: StatusCode,
+    pub(super) headers: HeaderMap,
+    /// Where the response finally came from, after redirects.
+    pub(super) url: Url,
+    /// DER of the leaf certificate of an `https` connection.
+    pub(super) peer_certificate: Option<Vec<u8>>,
+    body: Incoming,
+}
+
+impl CheckResponse {
+    pub(super) async fn text(self) -> Result<String, RequestError> {
+        let body = self.body.collect().await.map_err(RequestError::Body)?.to_bytes();
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+}
+
+#[derive(Debug)]
+pub(super) enum RequestError {
+    Invalid(String),
+    Send(Url, Box<legacy::Error>),
+    TooManyRedirects(Url),
+    Body(hyper::Error),
+    Timeout,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Invalid(message) => f.write_str(message),
+            RequestError::Send(url, _) => write!(f, "error sending request for url ({url})"),
+            RequestError::TooManyRedirects(url) => write!(f, "too many redirects, last to {url}"),
+            RequestError::Body(_) => f.write_str("error reading response body"),
+            RequestError::Timeout => f.write_str("operation timed out"),
+        }
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            RequestError::Send(_, e) => Some(e.as_ref()),
+            RequestError::Body(e) => Some(e),
+            _ => None,
+        }
+    }
+}
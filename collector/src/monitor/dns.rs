@@ -0,0 +1,302 @@
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::time::Instant;
+
+use chrono::Utc;
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig};
+use hickory_resolver::name_server::TokioConnectionProvider;
+use hickory_resolver::proto::ProtoErrorKind;
+use hickory_resolver::proto::rr::RecordType;
+use hickory_resolver::{Name, ResolveError, Resolver};
+use tokio::time;
+use tracing::warn;
+
+use crate::config::{DnsCheck, DnsRecordType, ResolvedMonitor};
+use crate::models::{CheckResult, ErrorType, truncate_error_message};
+
+pub async fn execute(monitor: &ResolvedMonitor, dns: &DnsCheck) -> CheckResult {
+    let start = Instant::now();
+    let outcome = match time::timeout(monitor.timeout, query(monitor, dns)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err((ErrorType::Timeout, format!("no answer within {:?}", monitor.timeout))),
+    };
+    let response_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (records, error) = match outcome {
+        Ok(records) => {
+            let error = check_answers(dns, &records).map(|message| (ErrorType::AnswerMismatch, message));
+            (Some(records), error)
+        }
+        Err(e) => (None, Some(e)),
+    };
+
+    if let Some((error_type, message)) = &error {
+        warn!(
+            project = monitor.project_id,
+            site = monitor.site_key,
+            error_type = error_type.as_str(),
+            error = message,
+            "check failed"
+        );
+    }
+    let (error_type, error_message) = error.unzip();
+
+    CheckResult {
+        project_id: monitor.project_id.clone(),
+        site_key: monitor.site_key.clone(),
+        url: monitor.url.clone(),
+        status_code: None,
+        response_ms,
+        is_up: error_type.is_none(),
+        error_type,
+        error_message: error_message.map(|m| truncate_error_message(&m)),
+        checked_at: Utc::now(),
+        is_retry: false,
+        in_maintenance: false,
+        ping: None,
+        dns_records: records,
+        tls: None,
+        timings: None,
+        degraded: false,
+    }
+}
+
+async fn query(monitor: &ResolvedMonitor, dns: &DnsCheck) -> Result<Vec<String>, (ErrorType, String)> {
+    let mut builder = match dns.resolver {
+        Some(addr) => Resolver::builder_with_config(
+            ResolverConfig::from_parts(None, vec![], NameServerConfigGroup::from_ips_clear(&[addr.ip()], addr.port(), true)),
+            TokioConnectionProvider::default(),
+        ),
+        None => Resolver::builder_tokio()
+            .map_err(|e| (ErrorType::DnsFailure, format!("failed to read system resolver config: {e}")))?,
+    };
+    // One attempt against a cold cache: retries are the scheduler's job.
+    let options = builder.options_mut();
+    options.timeout = monitor.timeout;
+    options.attempts = 1;
+    options.cache_size = 0;
+    let resolver = builder.build();
+
+    let mut name = Name::from_str(&dns.name)
+        .map_err(|e| (ErrorType::DnsFailure, format!("invalid name {:?}: {e}", dns.name)))?;
+    name.set_fqdn(true);
+    let record_type = record_type(dns.record_type);
+    match resolver.lookup(name, record_type).await {
+        Ok(lookup) => Ok(lookup
+            .record_iter()
+            .filter(|r| r.record_type() == record_type)
+            .map(|r| r.data().to_string())
+            .collect()),
+        Err(e) if e.is_no_records_found() && !e.is_nx_domain() => Ok(Vec::new()),
+        Err(e) => Err(classify(dns, e)),
+    }
+}
+
+fn classify(dns: &DnsCheck, e: ResolveError) -> (ErrorType, String) {
+    if e.is_nx_domain() {
+        return (ErrorType::DnsFailure, format!("{} does not exist (NXDOMAIN)", dns.name));
+    }
+    match e.proto().map(|p| p.kind()) {
+        Some(ProtoErrorKind::Timeout) => (ErrorType::Timeout, format!("query for {} timed out", dns.name)),
+        _ => (ErrorType::DnsFailure, format!("{} {} lookup failed: {e}", dns.name, dns.record_type.as_str())),
+    }
+}
+
+/// Returns why the answer doesn't satisfy the monitor, if it doesn't.
+fn check_answers(dns: &DnsCheck, records: &[String]) -> Option<String> {
+    if records.len() < dns.min_answers as usize {
+        return Some(format!(
+            "expected at least {} {} record(s) for {}, got {}",
+            dns.min_answers,
+            dns.record_type.as_str(),
+            dns.name,
+            records.len()
+        ));
+    }
+    let actual: Vec<String> = records.iter().map(|r| normalize(dns.record_type, r)).collect();
+    let missing: Vec<&String> = dns
+        .expected_answers
+        .iter()
+        .filter(|expected| !actual.contains(&normalize(dns.record_type, expected)))
+        .collect();
+    if missing.is_empty() {
+        None
+    } else {
+        Some(format!("missing {} record(s) {missing:?}, got {records:?}", dns.record_type.as_str()))
+    }
+}
+
+/// Canonical form for comparison: IP addresses re-printed, names lowercased without the root dot.
+fn normalize(record_type: DnsRecordType, value: &str) -> String {
+    match record_type {
+        DnsRecordType::A | DnsRecordType::Aaaa => {
+            value.parse::<IpAddr>().map(|ip| ip.to_string()).unwrap_or_else(|_| value.to_string())
+        }
+        DnsRecordType::Cname | DnsRecordType::Mx | DnsRecordType::Ns => {
+            value.trim_end_matches('.').to_ascii_lowercase()
+        }
+        DnsRecordType::Txt => value.to_string(),
+    }
+}
+
+fn record_type(record_type: DnsRecordType) -> RecordType {
+    match record_type {
+        DnsRecordType::A => RecordType::A,
+        DnsRecordType::Aaaa => RecordType::AAAA,
+        DnsRecordType::Cname => RecordType::CNAME,
+        DnsRecordType::Mx => RecordType::MX,
+        DnsRecordType::Txt => RecordType::TXT,
+        DnsRecordType::Ns => RecordType::NS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CheckSpec, test_monitor};
+    use hickory_resolver::proto::op::{Message, MessageType, ResponseCode};
+    use hickory_resolver::proto::rr::rdata::{A, CNAME, MX, TXT};
+    use hickory_resolver::proto::rr::{RData, Record};
+    use std::net::{Ipv4Addr, SocketAddr};
+    use std::time::Duration;
+    use tokio::net::UdpSocket;
+
+    /// Answers queries from a fixed zone over UDP; unknown names get NXDOMAIN.
+    async fn stub_server(zone: Vec<(&'static str, RData)>) -> SocketAddr {
+        let socket = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let addr = socket.local_addr().unwrap();
+        tokio::spawn(async move {
+            let mut buf = [0u8; 512];
+            loop {
+                let Ok((n, from)) = socket.recv_from(&mut buf).await else { return };
+                let request = Message::from_vec(&buf[..n]).unwrap();
+                let query = request.queries()[0].clone();
+                let mut response = Message::new();
+                response.set_id(request.id()).set_message_type(MessageType::Response).set_recursion_desired(true);
+                response.set_recursion_available(true).add_query(query.clone());
+                let name = query.name().to_ascii().trim_end_matches('.').to_string();
+                let matching: Vec<_> = zone.iter().filter(|(n, _)| *n == name).collect();
+                if matching.is_empty() {
+                    response.set_response_code(ResponseCode::NXDomain);
+                }
+                for (_, rdata) in matching.into_iter().filter(|(_, r)| r.record_type() == query.query_type()) {
+                    response.add_answer(Record::from_rdata(query.name().clone(), 300, rdata.clone()));
+                }
+                socket.send_to(&response.to_vec().unwrap(), from).await.unwrap();
+            }
+        });
+        addr
+    }
+
+    fn zone() -> Vec<(&'static str, RData)> {
+        let name = |s: &str| Name::from_str(s).unwrap();
+        vec![
+            ("example.test", RData::A(A::new(192, 0, 2, 1))),
+            ("example.test", RData::A(A::new(192, 0, 2, 2))),
+            ("example.test", RData::MX(MX::new(10, name("mx1.example.test.")))),
+            ("example.test", RData::TXT(TXT::new(vec!["v=spf1 -all".into()]))),
+            ("www.example.test", RData::CNAME(CNAME(name("example.test.")))),
+        ]
+    }
+
+    fn dns_check(resolver: SocketAddr, name: &str, record_type: DnsRecordType) -> DnsCheck {
+        DnsCheck {
+            name: name.into(),
+            record_type,
+            resolver: Some(resolver),
+            expected_answers: vec![],
+            min_answers: 1,
+        }
+    }
+
+    #[tokio::test]
+    async fn a_records_match_expected() {
+        let server = stub_server(zone()).await;
+        let mut dns = dns_check(server, "example.test", DnsRecordType::A);
+        dns.expected_answers = vec!["192.0.2.2".into(), "192.0.2.1".into()];
+        dns.min_answers = 2;
+        let result = execute(&test_monitor("dns://example.test"), &dns).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        assert_eq!(result.dns_records.unwrap(), vec!["192.0.2.1", "192.0.2.2"]);
+    }
+
+    #[tokio::test]
+    async fn names_compare_without_root_dot_or_case() {
+        let server = stub_server(zone()).await;
+        let mut dns = dns_check(server, "example.test", DnsRecordType::Mx);
+        dns.expected_answers = vec!["10 MX1.example.test".into()];
+        let result = execute(&test_monitor("dns://example.test"), &dns).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+
+        let mut dns = dns_check(server, "www.example.test", DnsRecordType::Cname);
+        dns.expected_answers = vec!["example.test".into()];
+        let result = execute(&test_monitor("dns://www.example.test"), &dns).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn missing_answer_is_a_mismatch() {
+        let server = stub_server(zone()).await;
+        let mut dns = dns_check(server, "example.test", DnsRecordType::A);
+        dns.expected_answers = vec!["192.0.2.9".into()];
+        let result = execute(&test_monitor("dns://example.test"), &dns).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "answer_mismatch");
+        assert!(result.error_message.unwrap().contains("192.0.2.9"));
+        assert_eq!(result.dns_records.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn too_few_answers() {
+        let server = stub_server(zone()).await;
+        let dns = dns_check(server, "example.test", DnsRecordType::Ns);
+        let result = execute(&test_monitor("dns://example.test"), &dns).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "answer_mismatch");
+        assert_eq!(
+            result.error_message.unwrap(),
+            "expected at least 1 NS record(s) for example.test, got 0"
+        );
+    }
+
+    #[tokio::test]
+    async fn txt_records_are_stored() {
+        let server = stub_server(zone()).await;
+        let dns = dns_check(server, "example.test", DnsRecordType::Txt);
+        let result = execute(&test_monitor("dns://example.test"), &dns).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        assert_eq!(result.dns_records.unwrap(), vec!["v=spf1 -all"]);
+    }
+
+    #[tokio::test]
+    async fn nxdomain_is_dns_failure() {
+        let server = stub_server(zone()).await;
+        let dns = dns_check(server, "missing.example.test", DnsRecordType::A);
+        let result = execute(&test_monitor("dns://missing.example.test"), &dns).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "dns_failure");
+        assert!(result.error_message.unwrap().contains("NXDOMAIN"));
+        assert!(result.dns_records.is_none());
+    }
+
+    #[tokio::test]
+    async fn silent_server_times_out() {
+        // Bound but never read, so queries go unanswered.
+        let socket = UdpSocket::bind((Ipv4Addr::LOCALHOST, 0)).await.unwrap();
+        let dns = dns_check(socket.local_addr().unwrap(), "example.test", DnsRecordType::A);
+        let mut monitor = test_monitor("dns://example.test");
+        monitor.timeout = Duration::from_millis(300);
+        let result = execute(&monitor, &dns).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "timeout");
+    }
+
+    #[tokio::test]
+    async fn dispatches_from_execute_check() {
+        let server = stub_server(zone()).await;
+        let mut monitor = test_monitor("dns://example.test");
+        monitor.check = CheckSpec::Dns(dns_check(server, "example.test", DnsRecordType::A));
+        let result = crate::monitor::execute_check(&crate::monitor::CheckClient::default(), &monitor).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+}
@@ -0,0 +1,277 @@
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::{UdpSocket, lookup_host};
+use tokio::time;
+use tracing::warn;
+
+use crate::config::{IcmpCheck, ResolvedMonitor};
+use crate::models::{CheckResult, ErrorType, PingStats};
+
+const ECHO_REQUEST_V4: u8 = 8;
+const ECHO_REPLY_V4: u8 = 0;
+const ECHO_REQUEST_V6: u8 = 128;
+const ECHO_REPLY_V6: u8 = 129;
+const PAYLOAD: &[u8] = b"upmon-icmp-check";
+/// Minimum gap between echo requests, so a fast host isn't flooded. Shrinks to fit the timeout.
+const ECHO_SPACING: Duration = Duration::from_millis(200);
+
+static NEXT_IDENTIFIER: AtomicU16 = AtomicU16::new(0);
+
+pub async fn execute(monitor: &ResolvedMonitor, icmp: &IcmpCheck) -> CheckResult {
+    let start = Instant::now();
+    let outcome = ping(monitor.timeout, icmp).await;
+    let elapsed_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (response_ms, ping_stats, error) = match outcome {
+        Ok(stats) => {
+            let response_ms = stats.rtt_avg_ms.map(|ms| ms.round() as i32).unwrap_or(elapsed_ms);
+            let error = if stats.packet_loss_pct > icmp.max_packet_loss_pct {
+                Some((
+                    ErrorType::PacketLoss,
+                    format!(
+                        "{:.0}% packet loss ({} of {} replies), threshold {}%",
+                        stats.packet_loss_pct, stats.received, stats.sent, icmp.max_packet_loss_pct
+                    ),
+                ))
+            } else {
+                None
+            };
+            (response_ms, Some(stats), error)
+        }
+        Err(e) => (elapsed_ms, None, Some(e)),
+    };
+
+    if let Some((error_type, message)) = &error {
+        warn!(
+            project = monitor.project_id,
+            site = monitor.site_key,
+            error_type = error_type.as_str(),
+            error = message,
+            "check failed"
+        );
+    }
+    let (error_type, error_message) = error.unzip();
+
+    CheckResult {
+        project_id: monitor.project_id.clone(),
+        site_key: monitor.site_key.clone(),
+        url: monitor.url.clone(),
+        status_code: None,
+        response_ms,
+        is_up: error_type.is_none(),
+        error_type,
+        error_message,
+        checked_at: Utc::now(),
+        is_retry: false,
+        in_maintenance: false,
+        ping: ping_stats,
+        dns_records: None,
+        tls: None,
+        timings: None,
+        degraded: false,
+    }
+}
+
+/// Sends `icmp.count` echo requests, all within `timeout`: each gets an equal share of what's left
+/// after resolving, covering both the wait for its reply and the spacing before the next.
+async fn ping(timeout: Duration, icmp: &IcmpCheck) -> Result<PingStats, (ErrorType, String)> {
+    let deadline = Instant::now() + timeout;
+    let addrs = time::timeout(timeout, lookup_host((icmp.host.as_str(), 0)))
+        .await
+        .map_err(|_| (ErrorType::Timeout, format!("failed to resolve {} within {timeout:?}", icmp.host)))?;
+    let addr = addrs
+        .ok()
+        .and_then(|mut addrs| addrs.next())
+        .map(|a| a.ip())
+        .ok_or_else(|| (ErrorType::HostUnreachable, format!("failed to resolve {}", icmp.host)))?;
+    let (socket, raw) = open_socket(addr)
+        .map_err(|e| (ErrorType::ConnectionError, format!("failed to open ICMP socket: {e}")))?;
+
+    let identifier = NEXT_IDENTIFIER.fetch_add(1, Ordering::Relaxed) ^ std::process::id() as u16;
+    let per_echo = deadline.saturating_duration_since(Instant::now()) / icmp.count;
+    let spacing = ECHO_SPACING.min(per_echo);
+    let mut rtts = Vec::new();
+    for sequence in 0..icmp.count as u16 {
+        let packet = echo_request(addr.is_ipv6(), identifier, sequence);
+        let sent_at = Instant::now();
+        if let Err(e) = socket.send_to(&packet, SocketAddr::new(addr, 0)).await {
+            let error_type = match e.kind() {
+                io::ErrorKind::HostUnreachable | io::ErrorKind::NetworkUnreachable => ErrorType::HostUnreachable,
+                _ => ErrorType::ConnectionError,
+            };
+            return Err((error_type, format!("failed to send echo request to {addr}: {e}")));
+        }
+        let reply = wait_reply(&socket, addr, raw, identifier, sequence);
+        if let Ok(Ok(())) = time::timeout(per_echo, reply).await {
+            rtts.push(sent_at.elapsed());
+        }
+        let spent = sent_at.elapsed();
+        if spent < spacing && sequence + 1 < icmp.count as u16 {
+            time::sleep(spacing - spent).await;
+        }
+    }
+    Ok(PingStats::from_rtts(icmp.count, &rtts))
+}
+
+/// Prefers an unprivileged datagram ICMP socket (Linux `net.ipv4.ping_group_range`),
+/// falling back to a raw socket when running with `CAP_NET_RAW`.
+fn open_socket(addr: IpAddr) -> io::Result<(UdpSocket, bool)> {
+    let (domain, protocol) = match addr {
+        IpAddr::V4(_) => (Domain::IPV4, Protocol::ICMPV4),
+        IpAddr::V6(_) => (Domain::IPV6, Protocol::ICMPV6),
+    };
+    let (socket, raw) = match Socket::new(domain, Type::DGRAM, Some(protocol)) {
+        Ok(socket) => (socket, false),
+        Err(_) => (Socket::new(domain, Type::RAW, Some(protocol))?, true),
+    };
+    socket.set_nonblocking(true)?;
+    Ok((UdpSocket::from_std(socket.into())?, raw))
+}
+
+async fn wait_reply(socket: &UdpSocket, addr: IpAddr, raw: bool, identifier: u16, sequence: u16) -> io::Result<()> {
+    let mut buf = [0u8; 1500];
+    loop {
+        let (n, from) = socket.recv_from(&mut buf).await?;
+        if from.ip() != addr {
+            continue;
+        }
+        // Raw IPv4 sockets deliver the IP header too; datagram sockets and IPv6 don't.
+        let mut packet = &buf[..n];
+        if raw && addr.is_ipv4() && !packet.is_empty() {
+            let header_len = ((packet[0] & 0x0f) as usize) * 4;
+            packet = packet.get(header_len..).unwrap_or_default();
+        }
+        // Datagram sockets rewrite the identifier, and the kernel only hands us our own replies.
+        if is_echo_reply(packet, addr.is_ipv6(), raw.then_some(identifier), sequence) {
+            return Ok(());
+        }
+    }
+}
+
+fn echo_request(ipv6: bool, identifier: u16, sequence: u16) -> Vec<u8> {
+    let mut packet = vec![if ipv6 { ECHO_REQUEST_V6 } else { ECHO_REQUEST_V4 }, 0, 0, 0];
+    packet.extend_from_slice(&identifier.to_be_bytes());
+    packet.extend_from_slice(&sequence.to_be_bytes());
+    packet.extend_from_slice(PAYLOAD);
+    // The kernel fills in ICMPv6 checksums, which cover a pseudo-header we don't see.
+    if !ipv6 {
+        let sum = checksum(&packet);
+        packet[2..4].copy_from_slice(&sum.to_be_bytes());
+    }
+    packet
+}
+
+fn is_echo_reply(packet: &[u8], ipv6: bool, identifier: Option<u16>, sequence: u16) -> bool {
+    if packet.len() < 8 || packet[0] != if ipv6 { ECHO_REPLY_V6 } else { ECHO_REPLY_V4 } {
+        return false;
+    }
+    let id = u16::from_be_bytes([packet[4], packet[5]]);
+    let seq = u16::from_be_bytes([packet[6], packet[7]]);
+    seq == sequence && identifier.is_none_or(|expected| id == expected)
+}
+
+/// RFC 1071 internet checksum.
+fn checksum(data: &[u8]) -> u16 {
+    let mut sum: u32 = data
+        .chunks(2)
+        .map(|c| u16::from_be_bytes([c[0], *c.get(1).unwrap_or(&0)]) as u32)
+        .sum();
+    while sum >> 16 != 0 {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+impl PingStats {
+    pub fn from_rtts(sent: u32, rtts: &[Duration]) -> Self {
+        let ms: Vec<f32> = rtts.iter().map(|d| d.as_secs_f32() * 1000.0).collect();
+        let received = ms.len() as u32;
+        let jitter_ms = (ms.len() >= 2).then(|| {
+            ms.windows(2).map(|w| (w[1] - w[0]).abs()).sum::<f32>() / (ms.len() - 1) as f32
+        });
+        Self {
+            sent,
+            received,
+            packet_loss_pct: (sent - received) as f32 * 100.0 / sent as f32,
+            rtt_min_ms: ms.iter().copied().reduce(f32::min),
+            rtt_avg_ms: (!ms.is_empty()).then(|| ms.iter().sum::<f32>() / ms.len() as f32),
+            rtt_max_ms: ms.iter().copied().reduce(f32::max),
+            jitter_ms,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::test_monitor;
+
+    #[test]
+    fn checksum_of_known_packet() {
+        // Echo request, id 1, seq 1, no payload: 0x0800 + 0x0001 + 0x0001 = 0x0802.
+        let packet = [8, 0, 0, 0, 0, 1, 0, 1];
+        assert_eq!(checksum(&packet), !0x0802);
+    }
+
+    #[test]
+    fn echo_request_checksum_verifies() {
+        let packet = echo_request(false, 0x1234, 7);
+        assert_eq!(packet[0], ECHO_REQUEST_V4);
+        assert_eq!(&packet[4..8], &[0x12, 0x34, 0, 7]);
+        assert_eq!(checksum(&packet), 0);
+    }
+
+    #[test]
+    fn reply_matching() {
+        let reply = [ECHO_REPLY_V4, 0, 0, 0, 0x12, 0x34, 0, 7];
+        assert!(is_echo_reply(&reply, false, Some(0x1234), 7));
+        assert!(is_echo_reply(&reply, false, None, 7));
+        assert!(!is_echo_reply(&reply, false, Some(0x4321), 7));
+        assert!(!is_echo_reply(&reply, false, None, 8));
+        assert!(!is_echo_reply(&reply, true, None, 7));
+        assert!(!is_echo_reply(&reply[..6], false, None, 7));
+    }
+
+    #[test]
+    fn stats_from_rtts() {
+        let rtts = [10, 14, 12].map(Duration::from_millis);
+        let stats = PingStats::from_rtts(4, &rtts);
+        assert_eq!(stats.received, 3);
+        assert_eq!(stats.packet_loss_pct, 25.0);
+        assert_eq!(stats.rtt_min_ms, Some(10.0));
+        assert_eq!(stats.rtt_max_ms, Some(14.0));
+        assert_eq!(stats.rtt_avg_ms, Some(12.0));
+        assert_eq!(stats.jitter_ms, Some(3.0));
+    }
+
+    #[test]
+    fn stats_with_no_replies() {
+        let stats = PingStats::from_rtts(3, &[]);
+        assert_eq!(stats.packet_loss_pct, 100.0);
+        assert!(stats.rtt_avg_ms.is_none());
+        assert!(stats.jitter_ms.is_none());
+    }
+
+    #[tokio::test]
+    #[ignore = "needs an ICMP socket: net.ipv4.ping_group_range or CAP_NET_RAW"]
+    async fn ping_localhost() {
+        let mut monitor = test_monitor("icmp://127.0.0.1");
+        monitor.timeout = Duration::from_millis(300);
+        let icmp = IcmpCheck { host: "127.0.0.1".into(), count: 3, max_packet_loss_pct: 0.0 };
+        let start = Instant::now();
+        let result = execute(&monitor, &icmp).await;
+        // Three echoes fit in the timeout with the spacing cut to 100 ms each.
+        assert!(start.elapsed() < Duration::from_millis(400), "{:?}", start.elapsed());
+        assert!(result.is_up, "{:?}", result.error_message);
+        let stats = result.ping.unwrap();
+        assert_eq!(stats.sent, 3);
+        assert_eq!(stats.received, 3);
+        assert_eq!(stats.packet_loss_pct, 0.0);
+        assert!(stats.rtt_avg_ms.is_some());
+    }
+}
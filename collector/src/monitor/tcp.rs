@@ -0,0 +1,228 @@
+use std::io;
+use std::time::Instant;
+
+use chrono::Utc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, lookup_host};
+use tokio::time;
+use tracing::warn;
+
+use crate::config::{ResolvedMonitor, TcpCheck, host_port};
+use crate::models::{CheckResult, ErrorType, truncate_error_message};
+
+/// Stop reading a banner that never contains the expected text after this many bytes.
+const MAX_BANNER_BYTES: usize = 4096;
+
+pub async fn execute(monitor: &ResolvedMonitor, tcp: &TcpCheck) -> CheckResult {
+    let start = Instant::now();
+    let outcome = match time::timeout(monitor.timeout, probe(tcp)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err((
+            ErrorType::Timeout,
+            format!("no answer from {} within {:?}", host_port(&tcp.host, tcp.port), monitor.timeout),
+        )),
+    };
+    let response_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (error_type, error_message) = match outcome {
+        Ok(()) => (None, None),
+        Err((error_type, message)) => {
+            warn!(
+                project = monitor.project_id,
+                site = monitor.site_key,
+                error_type = error_type.as_str(),
+                error = message,
+                "check failed"
+            );
+            (Some(error_type), Some(truncate_error_message(&message)))
+        }
+    };
+
+    CheckResult {
+        project_id: monitor.project_id.clone(),
+        site_key: monitor.site_key.clone(),
+        url: monitor.url.clone(),
+        status_code: None,
+        response_ms,
+        is_up: error_type.is_none(),
+        error_type,
+        error_message,
+        checked_at: Utc::now(),
+        is_retry: false,
+        in_maintenance: false,
+        ping: None,
+        dns_records: None,
+        tls: None,
+        timings: None,
+        degraded: false,
+    }
+}
+
+async fn probe(tcp: &TcpCheck) -> Result<(), (ErrorType, String)> {
+    let mut stream = connect(&tcp.host, tcp.port).await?;
+
+    if let Some(payload) = &tcp.send {
+        stream
+            .write_all(payload.as_bytes())
+            .await
+            .map_err(|e| (classify(&e), format!("failed to send payload: {e}")))?;
+    }
+
+    let Some(expected) = &tcp.expect else {
+        return Ok(());
+    };
+    let mut received = Vec::new();
+    let mut chunk = [0u8; 1024];
+    loop {
+        if String::from_utf8_lossy(&received).contains(expected.as_str()) {
+            return Ok(());
+        }
+        let n = if received.len() < MAX_BANNER_BYTES {
+            stream
+                .read(&mut chunk)
+                .await
+                .map_err(|e| (classify(&e), format!("failed to read banner: {e}")))?
+        } else {
+            0
+        };
+        if n == 0 {
+            return Err((
+                ErrorType::BannerMismatch,
+                format!("expected {expected:?}, got {:?}", String::from_utf8_lossy(&received)),
+            ));
+        }
+        received.extend_from_slice(&chunk[..n]);
+    }
+}
+
+/// Resolves `host` and connects to the first address that accepts.
+pub(super) async fn connect(host: &str, port: u16) -> Result<TcpStream, (ErrorType, String)> {
+    let target = host_port(host, port);
+    let addrs: Vec<_> = lookup_host(&target)
+        .await
+        .map_err(|e| (ErrorType::HostUnreachable, format!("failed to resolve {host}: {e}")))?
+        .collect();
+    TcpStream::connect(&addrs[..])
+        .await
+        .map_err(|e| (classify(&e), format!("failed to connect to {target}: {e}")))
+}
+
+pub(super) fn classify(e: &io::Error) -> ErrorType {
+    match e.kind() {
+        io::ErrorKind::ConnectionRefused => ErrorType::ConnectionRefused,
+        io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionAborted | io::ErrorKind::BrokenPipe => {
+            ErrorType::ConnectionReset
+        }
+        io::ErrorKind::HostUnreachable | io::ErrorKind::NetworkUnreachable => ErrorType::HostUnreachable,
+        io::ErrorKind::TimedOut => ErrorType::Timeout,
+        _ => ErrorType::ConnectionError,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CheckSpec, test_monitor};
+    use std::time::Duration;
+    use tokio::net::TcpListener;
+
+    fn make_monitor(port: u16, send: Option<&str>, expect: Option<&str>) -> (ResolvedMonitor, TcpCheck) {
+        let mut monitor = test_monitor(&format!("tcp://127.0.0.1:{port}"));
+        monitor.timeout = Duration::from_millis(500);
+        let tcp = TcpCheck {
+            host: "127.0.0.1".into(),
+            port,
+            send: send.map(String::from),
+            expect: expect.map(String::from),
+        };
+        (monitor, tcp)
+    }
+
+    /// Accepts one connection, optionally writes `banner`, then echoes whatever it reads.
+    async fn start_server(banner: &'static [u8]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(banner).await.unwrap();
+            let mut buf = [0u8; 256];
+            while let Ok(n) = stream.read(&mut buf).await {
+                if n == 0 || stream.write_all(&buf[..n]).await.is_err() {
+                    break;
+                }
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn connect_only_is_up() {
+        let port = start_server(b"").await;
+        let (monitor, tcp) = make_monitor(port, None, None);
+        let result = execute(&monitor, &tcp).await;
+        assert!(result.is_up);
+        assert!(result.status_code.is_none());
+        assert_eq!(result.url, format!("tcp://127.0.0.1:{port}"));
+    }
+
+    #[tokio::test]
+    async fn banner_match_is_up() {
+        let port = start_server(b"220 mail.example.com ESMTP\r\n").await;
+        let (monitor, tcp) = make_monitor(port, None, Some("ESMTP"));
+        let result = execute(&monitor, &tcp).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn reply_to_payload_is_matched() {
+        let port = start_server(b"").await;
+        let (monitor, tcp) = make_monitor(port, Some("PING\r\n"), Some("PING"));
+        let result = execute(&monitor, &tcp).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn banner_mismatch_on_close() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream.write_all(b"SSH-2.0-OpenSSH\r\n").await.unwrap();
+        });
+        let (monitor, tcp) = make_monitor(port, None, Some("220"));
+        let result = execute(&monitor, &tcp).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "banner_mismatch");
+        assert!(result.error_message.unwrap().contains("SSH-2.0-OpenSSH"));
+    }
+
+    #[tokio::test]
+    async fn silent_server_times_out() {
+        let port = start_server(b"").await;
+        let (monitor, tcp) = make_monitor(port, None, Some("220"));
+        let result = execute(&monitor, &tcp).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "timeout");
+    }
+
+    #[tokio::test]
+    async fn closed_port_is_refused() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let (monitor, tcp) = make_monitor(port, None, None);
+        let result = execute(&monitor, &tcp).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "connection_refused");
+    }
+
+    #[tokio::test]
+    async fn execute_check_dispatches_on_type() {
+        let port = start_server(b"").await;
+        let (mut monitor, tcp) = make_monitor(port, None, None);
+        monitor.check = CheckSpec::Tcp(tcp);
+        let result = crate::monitor::execute_check(&crate::monitor::CheckClient::default(), &monitor).await;
+        assert!(result.is_up);
+    }
+}
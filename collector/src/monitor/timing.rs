@@ -0,0 +1,227 @@
+use std::cell::RefCell;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use hyper::Uri;
+use hyper::http::uri::Scheme;
+use hyper_util::client::legacy::connect::{Connected, Connection};
+use hyper_util::rt::TokioIo;
+use rustls::client::Resumption;
+use rustls::crypto::ring;
+use rustls::pki_types::ServerName;
+use rustls::{ClientConfig, RootCertStore};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpStream, lookup_host};
+use tokio_rustls::TlsConnector;
+use tokio_rustls::client::TlsStream;
+use tower::Service;
+
+use crate::models::HttpTimings;
+
+use super::tls::AcceptAnyCert;
+
+// The connector opens each connection one phase at a time and stamps every phase on the clock
+// of whichever check is polling it.
+tokio::task_local! {
+    static CLOCK: RefCell<Clock>;
+}
+
+/// Connection setup time accumulated over every connect a request needed, redirects included.
+#[derive(Default)]
+pub(super) struct Clock {
+    dns: Option<Duration>,
+    connect: Option<Duration>,
+    tls: Option<Duration>,
+}
+
+impl Clock {
+    /// Splits `elapsed`, the time until response headers arrived, into phases.
+    pub(super) fn timings(&self, elapsed: Duration) -> HttpTimings {
+        let setup = [self.dns, self.connect, self.tls].into_iter().flatten().sum();
+        HttpTimings {
+            dns_ms: self.dns.map(millis),
+            connect_ms: self.connect.map(millis),
+            tls_ms: self.tls.map(millis),
+            ttfb_ms: Some(millis(elapsed.saturating_sub(setup))),
+            download_ms: None,
+        }
+    }
+}
+
+pub(super) fn millis(d: Duration) -> i32 {
+    d.as_millis().min(i32::MAX as u128) as i32
+}
+
+/// Runs `request` with a fresh clock, returning its output and the setup phases it went through.
+pub(super) async fn measure<F: Future>(request: F) -> (F::Output, Clock) {
+    CLOCK
+        .scope(RefCell::new(Clock::default()), async {
+            let output = request.await;
+            (output, CLOCK.with(|clock| clock.take()))
+        })
+        .await
+}
+
+/// Adds the time since `start` to one phase. A connect finishing in the background after its
+/// check moved on to a pooled connection has nowhere to record to.
+fn stamp(start: Instant, phase: impl FnOnce(&mut Clock) -> &mut Option<Duration>) {
+    let elapsed = start.elapsed();
+    let _ = CLOCK.try_with(|clock| {
+        let mut clock = clock.borrow_mut();
+        let phase = phase(&mut clock);
+        *phase = Some(phase.unwrap_or_default() + elapsed);
+    });
+}
+
+/// Resolver failure, kept apart from connect errors so checks can report it as such.
+#[derive(Debug)]
+pub(super) struct DnsError(io::Error);
+
+impl fmt::Display for DnsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dns error: {}", self.0)
+    }
+}
+
+impl Error for DnsError {}
+
+/// Leaf certificate the server presented, attached to every response on the connection.
+#[derive(Clone)]
+pub(super) struct PeerCertificate(pub(super) Vec<u8>);
+
+/// Opens check connections: resolves the host, connects and, for `https`, runs the TLS handshake,
+/// timing each step on its own.
+#[derive(Clone)]
+pub(super) struct TimedConnector(TlsConnector);
+
+impl TimedConnector {
+    pub(super) fn new(skip_verify: bool, fresh: bool) -> Self {
+        Self(TlsConnector::from(Arc::new(tls_config(skip_verify, fresh))))
+    }
+}
+
+impl Service<Uri> for TimedConnector {
+    type Response = TokioIo<Stream>;
+    type Error = Box<dyn Error + Send + Sync>;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, uri: Uri) -> Self::Future {
+        let tls = self.0.clone();
+        Box::pin(async move { connect(&tls, &uri).await.map(TokioIo::new) })
+    }
+}
+
+async fn connect(tls: &TlsConnector, uri: &Uri) -> Result<Stream, Box<dyn Error + Send + Sync>> {
+    let https = uri.scheme() == Some(&Scheme::HTTPS);
+    let host = uri.host().ok_or("URL has no host")?.trim_start_matches('[').trim_end_matches(']');
+    let port = uri.port_u16().unwrap_or(if https { 443 } else { 80 });
+
+    let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+        Ok(ip) => vec![SocketAddr::new(ip, port)],
+        Err(_) => {
+            let start = Instant::now();
+            let addrs = lookup_host((host, port)).await.map_err(DnsError)?.collect();
+            stamp(start, |clock| &mut clock.dns);
+            addrs
+        }
+    };
+
+    let start = Instant::now();
+    let tcp = TcpStream::connect(&addrs[..]).await?;
+    tcp.set_nodelay(true)?;
+    stamp(start, |clock| &mut clock.connect);
+    if !https {
+        return Ok(Stream::Plain(tcp));
+    }
+
+    let server_name = ServerName::try_from(host.to_string())?;
+    let start = Instant::now();
+    let stream = tls.connect(server_name, tcp).await?;
+    stamp(start, |clock| &mut clock.tls);
+    Ok(Stream::Tls(Box::new(stream)))
+}
+
+/// A check connection, encrypted for `https` URLs.
+pub(super) enum Stream {
+    Plain(TcpStream),
+    Tls(Box<TlsStream<TcpStream>>),
+}
+
+impl Connection for Stream {
+    fn connected(&self) -> Connected {
+        match self {
+            Stream::Plain(_) => Connected::new(),
+            Stream::Tls(stream) => match stream.get_ref().1.peer_certificates().and_then(|chain| chain.first()) {
+                Some(cert) => Connected::new().extra(PeerCertificate(cert.to_vec())),
+                None => Connected::new(),
+            },
+        }
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_read(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_read(cx, buf),
+        }
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_write(cx, buf),
+            Stream::Tls(stream) => Pin::new(stream).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_flush(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_flush(cx),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Stream::Plain(stream) => Pin::new(stream).poll_shutdown(cx),
+            Stream::Tls(stream) => Pin::new(stream).poll_shutdown(cx),
+        }
+    }
+}
+
+static WEBPKI_ROOTS: LazyLock<Arc<RootCertStore>> =
+    LazyLock::new(|| Arc::new(RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() }));
+
+/// TLS settings for a check client. Fresh clients keep no sessions, so every handshake is a full one.
+fn tls_config(skip_verify: bool, fresh: bool) -> ClientConfig {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions");
+    let mut config = if skip_verify {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+            .with_no_client_auth()
+    } else {
+        builder.with_root_certificates(WEBPKI_ROOTS.clone()).with_no_client_auth()
+    };
+    config.alpn_protocols = vec![b"http/1.1".to_vec()];
+    if fresh {
+        config.resumption = Resumption::disabled();
+    }
+    config
+}
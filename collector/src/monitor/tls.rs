@@ -0,0 +1,508 @@
+use std::net::IpAddr;
+use std::sync::{Arc, LazyLock};
+use std::time::Instant;
+
+use chrono::{DateTime, TimeDelta, Utc};
+use rustls::client::WebPkiServerVerifier;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio::time;
+use tokio_rustls::TlsConnector;
+use tracing::warn;
+use x509_parser::extensions::GeneralName;
+
+use crate::config::{CertExpiry, ResolvedMonitor, TlsCheck, host_port};
+use crate::models::{CheckResult, ErrorType, TlsCertificate, truncate_error_message};
+
+use super::tcp;
+
+/// Verifier against the bundled Mozilla roots, used to judge chains after the handshake.
+static WEBPKI_VERIFIER: LazyLock<Arc<WebPkiServerVerifier>> = LazyLock::new(|| {
+    let roots = RootCertStore { roots: webpki_roots::TLS_SERVER_ROOTS.to_vec() };
+    WebPkiServerVerifier::builder_with_provider(Arc::new(roots), Arc::new(ring::default_provider()))
+        .build()
+        .expect("failed to build certificate verifier")
+});
+
+pub async fn execute(monitor: &ResolvedMonitor, tls: &TlsCheck) -> CheckResult {
+    execute_with(monitor, tls, &WEBPKI_VERIFIER).await
+}
+
+async fn execute_with(monitor: &ResolvedMonitor, tls: &TlsCheck, verifier: &WebPkiServerVerifier) -> CheckResult {
+    let start = Instant::now();
+    let outcome = match time::timeout(monitor.timeout, handshake(tls)).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err((
+            ErrorType::Timeout,
+            format!("no handshake with {} within {:?}", host_port(&tls.host, tls.port), monitor.timeout),
+        )),
+    };
+    let response_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (cert, error) = match outcome {
+        Ok(chain) => match inspect(&chain[0], &tls.host, None) {
+            Ok(mut cert) => {
+                let chain_error = verify_chain(verifier, &chain, &tls.host).err();
+                cert.chain_valid = Some(chain_error.is_none());
+                let error = check_expiry(monitor, &mut cert, tls.cert).or_else(|| {
+                    if tls.tls_skip_verify {
+                        None
+                    } else if let Some(e) = chain_error {
+                        Some((ErrorType::TlsError, format!("certificate chain is not trusted: {e}")))
+                    } else if !cert.hostname_match {
+                        Some((
+                            ErrorType::TlsError,
+                            format!("certificate is not valid for {} (SANs: {})", tls.host, cert.sans.join(", ")),
+                        ))
+                    } else {
+                        None
+                    }
+                });
+                (Some(cert), error)
+            }
+            Err(e) => (None, Some((ErrorType::TlsError, e))),
+        },
+        Err(e) => (None, Some(e)),
+    };
+
+    if let Some((error_type, message)) = &error {
+        warn!(
+            project = monitor.project_id,
+            site = monitor.site_key,
+            error_type = error_type.as_str(),
+            error = message,
+            "check failed"
+        );
+    }
+    let (error_type, error_message) = error.unzip();
+
+    CheckResult {
+        project_id: monitor.project_id.clone(),
+        site_key: monitor.site_key.clone(),
+        url: monitor.url.clone(),
+        status_code: None,
+        response_ms,
+        is_up: error_type.is_none(),
+        error_type,
+        error_message: error_message.map(|m| truncate_error_message(&m)),
+        checked_at: Utc::now(),
+        is_retry: false,
+        in_maintenance: false,
+        ping: None,
+        dns_records: None,
+        tls: cert,
+        timings: None,
+        degraded: false,
+    }
+}
+
+/// Completes a handshake without judging the certificate, returning the presented chain.
+async fn handshake(tls: &TlsCheck) -> Result<Vec<CertificateDer<'static>>, (ErrorType, String)> {
+    let server_name = ServerName::try_from(tls.host.clone())
+        .map_err(|e| (ErrorType::TlsError, format!("invalid server name {:?}: {e}", tls.host)))?;
+    let stream = tcp::connect(&tls.host, tls.port).await?;
+
+    let provider = Arc::new(ring::default_provider());
+    let config = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+        .with_no_client_auth();
+    let stream = TlsConnector::from(Arc::new(config))
+        .connect(server_name, stream)
+        .await
+        .map_err(|e| (ErrorType::TlsError, format!("TLS handshake failed: {e}")))?;
+
+    match stream.get_ref().1.peer_certificates() {
+        Some(chain) if !chain.is_empty() => Ok(chain.iter().map(|c| c.clone().into_owned()).collect()),
+        _ => Err((ErrorType::TlsError, "server presented no certificate".into())),
+    }
+}
+
+/// Checks the chain against trusted roots, ignoring the name: hostname matching is reported separately.
+fn verify_chain(verifier: &WebPkiServerVerifier, chain: &[CertificateDer<'_>], host: &str) -> Result<(), String> {
+    let server_name = ServerName::try_from(host).map_err(|e| e.to_string())?;
+    match verifier.verify_server_cert(&chain[0], &chain[1..], &server_name, &[], UnixTime::now()) {
+        Ok(_) => Ok(()),
+        Err(rustls::Error::InvalidCertificate(
+            CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. },
+        )) => Ok(()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Parses the leaf certificate; `chain_valid` is filled in by the caller.
+pub(super) fn inspect(der: &[u8], host: &str, chain_valid: Option<bool>) -> Result<TlsCertificate, String> {
+    let (_, cert) =
+        x509_parser::parse_x509_certificate(der).map_err(|e| format!("failed to parse certificate: {e}"))?;
+    let timestamp = |t: &x509_parser::time::ASN1Time| DateTime::from_timestamp(t.timestamp(), 0).unwrap_or_default();
+    let sans: Vec<String> = match cert.subject_alternative_name() {
+        Ok(Some(ext)) => ext
+            .value
+            .general_names
+            .iter()
+            .filter_map(|name| match name {
+                GeneralName::DNSName(dns) => Some(dns.to_string()),
+                GeneralName::IPAddress(bytes) => <[u8; 4]>::try_from(*bytes)
+                    .map(IpAddr::from)
+                    .or_else(|_| <[u8; 16]>::try_from(*bytes).map(IpAddr::from))
+                    .ok()
+                    .map(|ip| ip.to_string()),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Ok(TlsCertificate {
+        subject: cert.subject().to_string(),
+        issuer: cert.issuer().to_string(),
+        not_before: timestamp(&cert.validity().not_before),
+        not_after: timestamp(&cert.validity().not_after),
+        hostname_match: hostname_matches(host, &sans),
+        sans,
+        chain_valid,
+        expiry_warning: false,
+    })
+}
+
+/// RFC 6125 matching: exact names, or a single leftmost `*` label.
+fn hostname_matches(host: &str, sans: &[String]) -> bool {
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    if let Ok(ip) = host.parse::<IpAddr>() {
+        return sans.iter().any(|san| san.parse::<IpAddr>() == Ok(ip));
+    }
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    sans.iter().any(|san| {
+        let san = san.to_ascii_lowercase();
+        match san.strip_prefix("*.") {
+            Some(suffix) => host.split_once('.').is_some_and(|(label, rest)| !label.is_empty() && rest == suffix),
+            None => san == host,
+        }
+    })
+}
+
+/// Flags a certificate inside `warn_days` and returns the error that fails the check, if any.
+pub(super) fn check_expiry(
+    monitor: &ResolvedMonitor,
+    cert: &mut TlsCertificate,
+    policy: CertExpiry,
+) -> Option<(ErrorType, String)> {
+    let remaining = cert.not_after - Utc::now();
+    cert.expiry_warning = remaining < TimeDelta::days(policy.warn_days.into());
+    if remaining <= TimeDelta::zero() {
+        return Some((ErrorType::CertExpired, format!("certificate expired at {}", cert.not_after.to_rfc3339())));
+    }
+    if remaining < TimeDelta::days(policy.min_days.into()) {
+        return Some((
+            ErrorType::CertExpiring,
+            format!(
+                "certificate expires in {} days at {}, minimum is {}",
+                remaining.num_days(),
+                cert.not_after.to_rfc3339(),
+                policy.min_days
+            ),
+        ));
+    }
+    if cert.expiry_warning {
+        warn!(
+            project = monitor.project_id,
+            site = monitor.site_key,
+            not_after = %cert.not_after,
+            days_left = remaining.num_days(),
+            "certificate expires soon"
+        );
+    }
+    None
+}
+
+/// Lets every handshake through so the certificate can be inspected rather than rejected.
+#[derive(Debug)]
+pub(super) struct AcceptAnyCert(pub(super) Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.0.signature_verification_algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CheckSpec, test_monitor};
+    use rcgen::{CertificateParams, DnType, IsCa, Issuer, KeyPair};
+    use rustls::ServerConfig;
+    use rustls::pki_types::PrivateKeyDer;
+    use std::net::SocketAddr;
+    use ::time::{Duration as TimeDuration, OffsetDateTime};
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    struct TestPki {
+        ca: CertificateDer<'static>,
+        leaf: CertificateDer<'static>,
+        key: PrivateKeyDer<'static>,
+    }
+
+    /// A CA and a leaf for `names`, valid for `days` from now.
+    fn pki(names: &[&str], days: i64) -> TestPki {
+        let ca_key = KeyPair::generate().unwrap();
+        let mut ca_params = CertificateParams::new(Vec::<String>::new()).unwrap();
+        ca_params.is_ca = IsCa::Ca(rcgen::BasicConstraints::Unconstrained);
+        ca_params.distinguished_name.push(DnType::CommonName, "Test CA");
+        let ca = ca_params.self_signed(&ca_key).unwrap();
+
+        let key = KeyPair::generate().unwrap();
+        let mut params = CertificateParams::new(names.iter().map(|n| n.to_string()).collect::<Vec<_>>()).unwrap();
+        params.distinguished_name.push(DnType::CommonName, names[0]);
+        params.not_before = OffsetDateTime::now_utc() - TimeDuration::days(1);
+        params.not_after = OffsetDateTime::now_utc() + TimeDuration::days(days);
+        let leaf = params.signed_by(&key, &Issuer::from_params(&ca_params, &ca_key)).unwrap();
+
+        TestPki {
+            ca: ca.der().clone(),
+            leaf: leaf.der().clone(),
+            key: PrivateKeyDer::try_from(key.serialize_der()).unwrap(),
+        }
+    }
+
+    fn verifier_trusting(ca: &CertificateDer<'static>) -> Arc<WebPkiServerVerifier> {
+        let mut roots = RootCertStore::empty();
+        roots.add(ca.clone()).unwrap();
+        WebPkiServerVerifier::builder_with_provider(Arc::new(roots), Arc::new(ring::default_provider()))
+            .build()
+            .unwrap()
+    }
+
+    async fn tls_server(pki: &TestPki) -> SocketAddr {
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![pki.leaf.clone()], pki.key.clone_key())
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Ok(mut tls) = acceptor.accept(stream).await {
+                    let _ = tls.shutdown().await;
+                }
+            }
+        });
+        addr
+    }
+
+    fn tls_check(host: &str, port: u16) -> TlsCheck {
+        TlsCheck {
+            host: host.into(),
+            port,
+            tls_skip_verify: false,
+            cert: CertExpiry { warn_days: 14, min_days: 0 },
+        }
+    }
+
+    #[tokio::test]
+    async fn trusted_certificate_is_recorded() {
+        let pki = pki(&["localhost"], 90);
+        let addr = tls_server(&pki).await;
+        let result =
+            execute_with(&test_monitor("tls://localhost"), &tls_check("localhost", addr.port()), &verifier_trusting(&pki.ca))
+                .await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        let cert = result.tls.unwrap();
+        assert_eq!(cert.subject, "CN=localhost");
+        assert_eq!(cert.issuer, "CN=Test CA");
+        assert_eq!(cert.sans, vec!["localhost"]);
+        assert_eq!(cert.chain_valid, Some(true));
+        assert!(cert.hostname_match);
+        assert!(!cert.expiry_warning);
+        assert_eq!((cert.not_after - Utc::now()).num_days(), 89);
+    }
+
+    #[tokio::test]
+    async fn untrusted_chain_fails_unless_skipped() {
+        let pki = pki(&["localhost"], 90);
+        let addr = tls_server(&pki).await;
+        let monitor = test_monitor("tls://localhost");
+        let mut tls = tls_check("localhost", addr.port());
+        let result = execute(&monitor, &tls).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "tls_error");
+        assert!(result.error_message.unwrap().contains("not trusted"));
+        assert_eq!(result.tls.unwrap().chain_valid, Some(false));
+
+        tls.tls_skip_verify = true;
+        let result = execute(&monitor, &tls).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+    }
+
+    #[tokio::test]
+    async fn hostname_mismatch_fails() {
+        let pki = pki(&["other.example"], 90);
+        let addr = tls_server(&pki).await;
+        let result =
+            execute_with(&test_monitor("tls://localhost"), &tls_check("localhost", addr.port()), &verifier_trusting(&pki.ca))
+                .await;
+        assert!(!result.is_up);
+        assert!(result.error_message.unwrap().contains("not valid for localhost"));
+        let cert = result.tls.unwrap();
+        assert_eq!(cert.chain_valid, Some(true));
+        assert!(!cert.hostname_match);
+    }
+
+    #[tokio::test]
+    async fn expiry_thresholds() {
+        let pki = pki(&["localhost"], 10);
+        let addr = tls_server(&pki).await;
+        let verifier = verifier_trusting(&pki.ca);
+        let monitor = test_monitor("tls://localhost");
+        let mut tls = tls_check("localhost", addr.port());
+
+        let result = execute_with(&monitor, &tls, &verifier).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        assert!(result.tls.unwrap().expiry_warning);
+
+        tls.cert.min_days = 30;
+        let result = execute_with(&monitor, &tls, &verifier).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "cert_expiring");
+        assert!(result.error_message.unwrap().starts_with("certificate expires in 9 days"));
+    }
+
+    #[tokio::test]
+    async fn expired_certificate() {
+        let pki = pki(&["localhost"], -1);
+        let addr = tls_server(&pki).await;
+        let result =
+            execute_with(&test_monitor("tls://localhost"), &tls_check("localhost", addr.port()), &verifier_trusting(&pki.ca))
+                .await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "cert_expired");
+    }
+
+    #[tokio::test]
+    async fn refused_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+        let result = execute(&test_monitor("tls://localhost"), &tls_check("127.0.0.1", port)).await;
+        assert!(!result.is_up);
+        assert_eq!(result.error_type.unwrap().as_str(), "connection_refused");
+        assert!(result.tls.is_none());
+    }
+
+    #[tokio::test]
+    async fn dispatches_from_execute_check() {
+        let pki = pki(&["localhost"], 90);
+        let addr = tls_server(&pki).await;
+        let mut monitor = test_monitor("tls://localhost");
+        let mut tls = tls_check("localhost", addr.port());
+        tls.tls_skip_verify = true;
+        monitor.check = CheckSpec::Tls(tls);
+        let result = crate::monitor::execute_check(&crate::monitor::CheckClient::default(), &monitor).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        assert!(!result.degraded);
+        assert!(result.tls.is_some());
+    }
+
+    #[tokio::test]
+    async fn certificate_inside_warn_days_degrades_check() {
+        let pki = pki(&["localhost"], 10);
+        let addr = tls_server(&pki).await;
+        let mut monitor = test_monitor("tls://localhost");
+        let mut tls = tls_check("localhost", addr.port());
+        tls.tls_skip_verify = true;
+        monitor.check = CheckSpec::Tls(tls);
+        let result = crate::monitor::execute_check(&crate::monitor::CheckClient::default(), &monitor).await;
+        assert!(result.is_up, "{:?}", result.error_message);
+        assert!(result.degraded);
+        assert_eq!(result.error_type.unwrap().as_str(), "cert_expiring");
+        assert!(result.error_message.unwrap().starts_with("certificate expires in 9 days"));
+    }
+
+    #[tokio::test]
+    async fn https_check_captures_certificate() {
+        let pki = pki(&["localhost"], 10);
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![pki.leaf.clone()], pki.key.clone_key())
+            .unwrap();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut tls = acceptor.accept(stream).await.unwrap();
+            let mut buf = [0u8; 1024];
+            let _ = tokio::io::AsyncReadExt::read(&mut tls, &mut buf).await;
+            tls.write_all(b"HTTP/1.1 200 OK\r\ncontent-length: 0\r\nconnection: close\r\n\r\n").await.unwrap();
+            let _ = tls.shutdown().await;
+        });
+
+        let mut monitor = test_monitor(&format!("https://localhost:{port}/"));
+        let CheckSpec::Http(http) = &mut monitor.check else { unreachable!() };
+        http.tls_skip_verify = true;
+        http.cert.min_days = 30;
+        let clients = crate::monitor::HttpClients::new(std::time::Duration::from_secs(5));
+        let result = crate::monitor::execute_check(clients.for_check(&monitor.check), &monitor).await;
+        assert!(!result.is_up);
+        assert_eq!(result.status_code, Some(200));
+        assert_eq!(result.error_type.unwrap().as_str(), "cert_expiring");
+        let cert = result.tls.unwrap();
+        assert_eq!(cert.chain_valid, None);
+        assert!(cert.hostname_match);
+        assert!(cert.expiry_warning);
+        let timings = result.timings.unwrap();
+        assert!(timings.dns_ms.is_some());
+        assert!(timings.tls_ms.is_some());
+    }
+
+    #[test]
+    fn hostname_matching() {
+        let sans = vec!["example.com".to_string(), "*.example.net".to_string(), "10.0.0.1".to_string()];
+        assert!(hostname_matches("example.com", &sans));
+        assert!(hostname_matches("EXAMPLE.com.", &sans));
+        assert!(hostname_matches("www.example.net", &sans));
+        assert!(!hostname_matches("example.net", &sans));
+        assert!(!hostname_matches("a.b.example.net", &sans));
+        assert!(hostname_matches("10.0.0.1", &sans));
+        assert!(!hostname_matches("10.0.0.2", &sans));
+        assert!(!hostname_matches("[::1]", &sans));
+    }
+}
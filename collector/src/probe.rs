@@ -0,0 +1,219 @@
+use std::fmt::Write;
+use std::time::{Duration, Instant};
+
+use crate::config::{CheckSpec, ResolvedMonitor};
+use crate::metrics::{header, seconds};
+use crate::models::CheckResult;
+use crate::monitor::{self, HttpClients};
+use crate::validate;
+
+/// Checks `target` once with the settings of `module` and renders the outcome the way
+/// blackbox_exporter does. Nothing is stored, alerted on or counted in `/metrics`.
+/// `timeout` caps the module's own, leaving room for the scrape to finish.
+pub async fn run(
+    clients: &HttpClients,
+    module: &ResolvedMonitor,
+    target: &str,
+    timeout: Option<Duration>,
+) -> Result<String, String> {
+    let mut monitor = aim(module, target)?;
+    if let Some(timeout) = timeout {
+        monitor.timeout = monitor.timeout.min(timeout);
+    }
+    let start = Instant::now();
+    let result = monitor::execute_check(clients.for_check(&monitor.check), &monitor).await;
+    Ok(render(&monitor, &result, start.elapsed()))
+}
+
+/// A copy of `module` checking `target`: a URL for `http` modules, `http://` assumed without a scheme;
+/// `host` or `host:port` for `tcp` and `tls`; a host for `icmp`; the name to query for `dns`.
+pub fn aim(module: &ResolvedMonitor, target: &str) -> Result<ResolvedMonitor, String> {
+    let target = target.trim();
+    if target.is_empty() {
+        return Err("target is required".into());
+    }
+    let mut monitor = module.clone();
+    match &mut monitor.check {
+        CheckSpec::Http(http) => {
+            http.url = if target.contains("://") { target.to_string() } else { format!("http://{target}") };
+            if let Some(message) = validate::check_url(&http.url) {
+                return Err(message);
+            }
+        }
+        CheckSpec::Tcp(tcp) => {
+            let (host, port) = split_host_port(target)?;
+            tcp.host = host;
+            tcp.port = port.unwrap_or(tcp.port);
+            if tcp.port == 0 {
+                return Err(format!("target {target:?} needs a port, module {} has none", module.site_key));
+            }
+        }
+        CheckSpec::Icmp(icmp) => icmp.host = target.to_string(),
+        CheckSpec::Dns(dns) => dns.name = target.to_string(),
+        CheckSpec::Tls(tls) => {
+            let (host, port) = split_host_port(target)?;
+            tls.host = host;
+            tls.port = port.unwrap_or(tls.port);
+        }
+    }
+    monitor.url = monitor.redact(&monitor.check.target_url());
+    Ok(monitor)
+}
+
+/// Splits `host`, `host:port` or `[ipv6]:port`. A bare IPv6 address has no port.
+fn split_host_port(target: &str) -> Result<(String, Option<u16>), String> {
+    let (host, port) = match target.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((host, "")) => (host, None),
+            Some((host, port)) => match port.strip_prefix(':') {
+                Some(port) => (host, Some(port)),
+                None => return Err(format!("invalid target {target:?}")),
+            },
+            None => return Err(format!("invalid target {target:?}")),
+        },
+        None => match target.split_once(':') {
+            Some((host, port)) if !port.contains(':') => (host, Some(port)),
+            _ => (target, None),
+        },
+    };
+    if host.is_empty() {
+        return Err(format!("invalid target {target:?}"));
+    }
+    let port = match port {
+        Some(port) => match port.parse::<u16>() {
+            Ok(port) if port > 0 => Some(port),
+            _ => return Err(format!("invalid port {port:?}")),
+        },
+        None => None,
+    };
+    Ok((host.to_string(), port))
+}
+
+/// blackbox_exporter's series for the parts of `result` its check type produces,
+/// plus `probe_degraded` for `response_warn_ms` and `cert_warn_days`.
+fn render(monitor: &ResolvedMonitor, result: &CheckResult, duration: Duration) -> String {
+    let mut out = String::new();
+    let flag = |b: bool| if b { 1 } else { 0 };
+
+    header(&mut out, "probe_success", "Whether the probe succeeded.", "gauge");
+    let _ = writeln!(out, "probe_success {}", flag(result.is_up));
+    header(&mut out, "probe_duration_seconds", "How long the probe took to complete in seconds.", "gauge");
+    let _ = writeln!(out, "probe_duration_seconds {}", duration.as_secs_f64());
+    header(&mut out, "probe_degraded", "Whether the probe succeeded but hit response_warn_ms or cert_warn_days.", "gauge");
+    let _ = writeln!(out, "probe_degraded {}", flag(result.degraded));
+
+    if matches!(monitor.check, CheckSpec::Http(_)) {
+        header(&mut out, "probe_http_status_code", "Response HTTP status code, 0 without a response.", "gauge");
+        let _ = writeln!(out, "probe_http_status_code {}", result.status_code.unwrap_or(0));
+    }
+    if let Some(timings) = &result.timings {
+        header(&mut out, "probe_http_duration_seconds", "Duration of the HTTP request by phase.", "gauge");
+        for (phase, ms) in [
+            ("resolve", timings.dns_ms),
+            ("connect", timings.connect_ms),
+            ("tls", timings.tls_ms),
+            ("processing", timings.ttfb_ms),
+            ("transfer", timings.download_ms),
+        ] {
+            let _ = writeln!(
+                out,
+                "probe_http_duration_seconds{{phase=\"{phase}\"}} {}",
+                f64::from(ms.unwrap_or(0)) / 1000.0
+            );
+        }
+    }
+    if let Some(cert) = &result.tls {
+        header(&mut out, "probe_ssl_earliest_cert_expiry", "Unix time the served certificate expires.", "gauge");
+        let _ = writeln!(out, "probe_ssl_earliest_cert_expiry {}", seconds(cert.not_after));
+    }
+    if let Some(records) = &result.dns_records {
+        header(&mut out, "probe_dns_answer_rrs", "Number of records in the answer.", "gauge");
+        let _ = writeln!(out, "probe_dns_answer_rrs {}", records.len());
+    }
+    if let Some(ping) = &result.ping {
+        header(&mut out, "probe_icmp_packet_loss_ratio", "Share of echo requests that got no reply.", "gauge");
+        let _ = writeln!(out, "probe_icmp_packet_loss_ratio {}", f64::from(ping.packet_loss_pct) / 100.0);
+        if let Some(rtt_ms) = ping.rtt_avg_ms {
+            header(&mut out, "probe_icmp_duration_seconds", "Mean round-trip time of the echo requests.", "gauge");
+            let _ = writeln!(out, "probe_icmp_duration_seconds{{phase=\"rtt\"}} {}", f64::from(rtt_ms) / 1000.0);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{Config, test_monitor};
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn modules(json: &str) -> std::collections::BTreeMap<String, ResolvedMonitor> {
+        let mut config: Config = serde_json::from_str(json).unwrap();
+        config.resolve_probe_modules()
+    }
+
+    #[test]
+    fn aims_modules_at_targets() {
+        let modules = modules(
+            r#"{
+                "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+                "projects": [],
+                "probe_modules": { "smtp": { "type": "tcp", "port": 25, "expect": "220 " } }
+            }"#,
+        );
+        assert_eq!(aim(&modules["http_2xx"], "example.com/health").unwrap().url, "http://example.com/health");
+        assert_eq!(aim(&modules["tls"], "example.com").unwrap().url, "tls://example.com:443");
+        assert_eq!(aim(&modules["tls"], "[::1]:8443").unwrap().url, "tls://[::1]:8443");
+        assert_eq!(aim(&modules["smtp"], "mail.example.com").unwrap().url, "tcp://mail.example.com:25");
+        assert_eq!(aim(&modules["smtp"], "mail.example.com:2525").unwrap().url, "tcp://mail.example.com:2525");
+        assert_eq!(aim(&modules["dns"], "example.com").unwrap().url, "dns:example.com?type=A");
+        assert!(aim(&modules["tcp_connect"], "example.com").is_err());
+        assert!(aim(&modules["tcp_connect"], "example.com:http").is_err());
+        assert!(aim(&modules["http_2xx"], "ftp://example.com").is_err());
+        assert!(aim(&modules["icmp"], " ").is_err());
+    }
+
+    #[tokio::test]
+    async fn renders_http_probe() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(204)).mount(&server).await;
+        let clients = HttpClients::new(Duration::from_secs(5));
+        let module = modules(r#"{ "defaults": { "interval_sec": 60, "timeout_sec": 10 }, "projects": [] }"#)
+            .remove("http_2xx")
+            .unwrap();
+
+        let text = run(&clients, &module, &server.uri(), None).await.unwrap();
+        assert!(text.lines().any(|l| l == "probe_success 1"), "{text}");
+        assert!(text.lines().any(|l| l == "probe_http_status_code 204"), "{text}");
+        assert!(text.contains("probe_http_duration_seconds{phase=\"connect\"}"));
+
+        // A plain monitor expects exactly 200.
+        let text = run(&clients, &test_monitor(""), &server.uri(), None).await.unwrap();
+        assert!(text.lines().any(|l| l == "probe_success 0"), "{text}");
+    }
+
+    #[tokio::test]
+    async fn default_request_settings_stay_home() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET")).respond_with(ResponseTemplate::new(200)).mount(&server).await;
+        let module = modules(
+            r#"{
+                "defaults": {
+                    "interval_sec": 60, "timeout_sec": 10,
+                    "headers": { "Authorization": "Bearer org-token" }, "query": { "api_key": "org-key" }
+                },
+                "projects": [],
+                "probe_modules": { "api": { "headers": { "X-Probe": "1" } } }
+            }"#,
+        )
+        .remove("api")
+        .unwrap();
+
+        run(&HttpClients::new(Duration::from_secs(5)), &module, &server.uri(), None).await.unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert!(!requests[0].headers.contains_key("authorization"));
+        assert_eq!(requests[0].headers["x-probe"], "1");
+        assert_eq!(requests[0].url.query(), None);
+    }
+}
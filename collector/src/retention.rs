@@ -0,0 +1,61 @@
+use std::time::{Duration, Instant};
+
+use chrono::Utc;
+use sqlx::PgPool;
+use tokio::sync::watch;
+use tokio::time;
+use tracing::{error, info};
+
+use crate::db;
+
+const PRUNE_INTERVAL: Duration = Duration::from_secs(3600);
+
+/// Deletes `monitor_checks` rows older than the configured retention, once per
+/// `PRUNE_INTERVAL` and immediately whenever a config reload changes the value.
+pub async fn run_prune_loop(pool: PgPool, mut retention_days: watch::Receiver<u32>) {
+    let mut ticker = time::interval(PRUNE_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {}
+            changed = retention_days.changed() => {
+                if changed.is_err() {
+                    return;
+                }
+                ticker.reset();
+            }
+        }
+        let days = *retention_days.borrow_and_update();
+        prune(&pool, days).await;
+    }
+}
+
+async fn prune(pool: &PgPool, retention_days: u32) {
+    let executed_at = Utc::now();
+    let start = Instant::now();
+    let result = db::delete_checks_older_than(pool, retention_days).await;
+    let duration_ms = start.elapsed().as_millis().min(i32::MAX as u128) as i32;
+
+    let (deleted_count, error_message) = match result {
+        Ok(deleted) => {
+            info!(retention_days, deleted, duration_ms, "pruned monitor checks");
+            (Some(deleted as i64), None)
+        }
+        Err(e) => {
+            error!(retention_days, error = %e, "failed to prune monitor checks");
+            (None, Some(e.to_string()))
+        }
+    };
+
+    if let Err(e) = db::insert_retention_run(
+        pool,
+        executed_at,
+        retention_days,
+        deleted_count,
+        duration_ms,
+        error_message.as_deref(),
+    )
+    .await
+    {
+        error!(error = %e, "failed to record retention run");
+    }
+}
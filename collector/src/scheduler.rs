@@ -1,42 +1,191 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use chrono::{DateTime, Utc};
 use notify::event::EventKind;
 use notify::{Watcher, RecursiveMode, recommended_watcher};
-use reqwest::Client;
 use sqlx::PgPool;
-use tokio::sync::mpsc;
+use tokio::sync::{mpsc, oneshot, watch};
 use tokio::time;
-use tracing::{info, error};
+use tracing::{info, error, warn};
 
-use crate::config::{self, MonitorKey, ResolvedMonitor};
+use crate::alert::{self, Alert};
+use crate::config::{self, ConfigError, MonitorKey, ResolvedMonitor};
 use crate::db;
-use crate::monitor;
+use crate::maintenance;
+use crate::metrics::Metrics;
+use crate::models::{CheckResult, IncidentKind};
+use crate::monitor::{self, HttpClients};
+use crate::probe;
+use crate::state::{CheckStatus, MonitorState, Transition};
+use crate::trigger::Scope;
 
 type MonitorMap = Arc<Mutex<HashMap<MonitorKey, Arc<ResolvedMonitor>>>>;
+type RuntimeMap = Arc<Mutex<HashMap<MonitorKey, MonitorRuntime>>>;
+type TriggerMap = Arc<Mutex<HashMap<MonitorKey, mpsc::UnboundedSender<Command>>>>;
 
-pub struct MonitorManager {
+/// Sent to a monitor's loop between checks.
+enum Command {
+    /// Check right away and send the result back.
+    CheckNow(oneshot::Sender<CheckResult>),
+    /// Whether the monitor is paused may have changed.
+    Wake,
+}
+
+/// What a monitor's loop has done so far, kept for the admin API.
+#[derive(Clone, Default)]
+pub struct MonitorRuntime {
+    pub state: MonitorState,
+    pub last_result: Option<CheckResult>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub paused: bool,
+}
+
+/// Maps shared between the manager and every monitor loop.
+#[derive(Clone, Default)]
+struct Shared {
     monitors: MonitorMap,
+    runtime: RuntimeMap,
+    triggers: TriggerMap,
+    /// Monitors paused through the admin API.
+    paused: Arc<Mutex<HashSet<MonitorKey>>>,
+    metrics: Arc<Metrics>,
+}
+
+impl Shared {
+    /// Paused in config or at runtime; `false` for monitors that no longer exist.
+    fn is_paused(&self, key: &MonitorKey) -> bool {
+        let in_config = self.monitors.lock().unwrap().get(key).is_some_and(|m| m.paused);
+        in_config || self.paused.lock().unwrap().contains(key)
+    }
+
+    fn wake(&self, key: &MonitorKey) {
+        if let Some(commands) = self.triggers.lock().unwrap().get(key) {
+            let _ = commands.send(Command::Wake);
+        }
+    }
+}
+
+pub struct MonitorManager {
+    shared: Shared,
     pool: PgPool,
-    client: Client,
-    insecure_client: Client,
+    clients: HttpClients,
+    retention_days: watch::Sender<u32>,
+    probe_modules: Mutex<Arc<BTreeMap<String, ResolvedMonitor>>>,
 }
 
 impl MonitorManager {
-    pub fn new(pool: PgPool, client: Client, insecure_client: Client) -> Self {
+    pub fn new(pool: PgPool, clients: HttpClients, retention_days: watch::Sender<u32>) -> Self {
         Self {
-            monitors: Arc::new(Mutex::new(HashMap::new())),
+            shared: Shared::default(),
             pool,
-            client,
-            insecure_client,
+            clients,
+            retention_days,
+            probe_modules: Mutex::default(),
+        }
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.shared.metrics.started_at()
+    }
+
+    pub fn metrics(&self) -> &Metrics {
+        &self.shared.metrics
+    }
+
+    /// Every running monitor with what its loop last reported, ordered by key.
+    pub fn snapshot(&self) -> Vec<(Arc<ResolvedMonitor>, MonitorRuntime)> {
+        let monitors: Vec<Arc<ResolvedMonitor>> = self.shared.monitors.lock().unwrap().values().cloned().collect();
+        let runtime = self.shared.runtime.lock().unwrap();
+        let mut snapshot: Vec<_> = monitors
+            .into_iter()
+            .map(|m| {
+                let state = runtime.get(&m.key()).cloned().unwrap_or_default();
+                (m, state)
+            })
+            .collect();
+        snapshot.sort_by(|(a, _), (b, _)| (&a.project_id, &a.site_key).cmp(&(&b.project_id, &b.site_key)));
+        snapshot
+    }
+
+    /// Checks every monitor in `scope` right away and waits for the results, ordered by key.
+    /// A monitor that is already mid-check answers with that check instead of running another;
+    /// paused monitors are left out.
+    pub async fn check_now(&self, scope: &Scope) -> Vec<CheckResult> {
+        let mut pending: Vec<(MonitorKey, oneshot::Receiver<CheckResult>)> = self
+            .shared
+            .triggers
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| scope.matches(key))
+            .filter_map(|(key, commands)| {
+                let (reply, result) = oneshot::channel();
+                commands.send(Command::CheckNow(reply)).ok().map(|()| (key.clone(), result))
+            })
+            .collect();
+        pending.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut results = Vec::new();
+        for (_, result) in pending {
+            // The monitor was paused or removed before it got to the check.
+            if let Ok(result) = result.await {
+                results.push(result);
+            }
         }
+        results
+    }
+
+    /// `Some(paused)` for a known monitor, counting a pause from either config or the admin API.
+    pub fn is_paused(&self, key: &MonitorKey) -> Option<bool> {
+        let known = self.shared.monitors.lock().unwrap().contains_key(key);
+        known.then(|| self.shared.is_paused(key))
+    }
+
+    /// Loads the runtime pauses that survived a restart; call before `start_initial`.
+    pub async fn restore_pauses(&self) {
+        match db::fetch_monitor_pauses(&self.pool).await {
+            Ok(keys) => {
+                info!(count = keys.len(), "runtime pauses restored");
+                self.shared.paused.lock().unwrap().extend(keys);
+            }
+            Err(e) => error!(error = %e, "failed to load runtime pauses"),
+        }
+    }
+
+    /// Pauses or resumes a monitor until told otherwise, persisting across restarts. Returns whether the
+    /// monitor ends up paused, which it stays while its config says so; `None` for unknown monitors.
+    pub async fn set_paused(&self, key: &MonitorKey, paused: bool) -> Result<Option<bool>, sqlx::Error> {
+        if !self.shared.monitors.lock().unwrap().contains_key(key) {
+            return Ok(None);
+        }
+        if paused {
+            db::insert_monitor_pause(&self.pool, key, Utc::now()).await?;
+            self.shared.paused.lock().unwrap().insert(key.clone());
+        } else {
+            db::delete_monitor_pause(&self.pool, key).await?;
+            self.shared.paused.lock().unwrap().remove(key);
+        }
+        info!(project = %key.0, site = %key.1, paused, "runtime pause changed");
+        self.shared.wake(key);
+        Ok(Some(self.shared.is_paused(key)))
+    }
+
+    pub fn set_probe_modules(&self, modules: BTreeMap<String, ResolvedMonitor>) {
+        *self.probe_modules.lock().unwrap() = Arc::new(modules);
+    }
+
+    /// Runs probe module `module` against `target` right away, independent of every monitor.
+    pub async fn probe(&self, module: &str, target: &str, timeout: Option<Duration>) -> Result<String, String> {
+        let modules = self.probe_modules.lock().unwrap().clone();
+        let module = modules.get(module).ok_or_else(|| format!("unknown module {module:?}"))?;
+        probe::run(&self.clients, module, target, timeout).await
     }
 
     pub fn start_initial(&self, monitors: Vec<ResolvedMonitor>) {
         let delays = stagger_delays(&monitors);
-        let mut map = self.monitors.lock().unwrap();
+        let mut map = self.shared.monitors.lock().unwrap();
         for (m, delay) in monitors.into_iter().zip(delays) {
             let key = m.key();
             map.insert(key.clone(), Arc::new(m));
@@ -50,7 +199,7 @@ impl MonitorManager {
             .map(|m| (m.key(), m))
             .collect();
 
-        let mut map = self.monitors.lock().unwrap();
+        let mut map = self.shared.monitors.lock().unwrap();
 
         let removed: Vec<MonitorKey> = map.keys()
             .filter(|k| !new_map.contains_key(k))
@@ -66,7 +215,8 @@ impl MonitorManager {
                 Some(existing) if existing.as_ref() == &monitor => {}
                 Some(_) => {
                     info!(project = %key.0, site = %key.1, "updating monitor config");
-                    map.insert(key, Arc::new(monitor));
+                    map.insert(key.clone(), Arc::new(monitor));
+                    self.shared.wake(&key);
                 }
                 None => {
                     info!(project = %key.0, site = %key.1, "starting new monitor");
@@ -77,13 +227,58 @@ impl MonitorManager {
         }
     }
 
+    async fn reload_config(&self, config_path: &Path) {
+        let reloaded_at = Utc::now();
+        let applied = self.apply_config(config_path);
+        self.shared.metrics.record_config_reload(applied.is_ok());
+        let recorded = match applied {
+            Ok(count) => {
+                info!("config reload complete");
+                db::insert_config_reload(&self.pool, reloaded_at, Some(count as i32), None, None).await
+            }
+            Err(e) => {
+                error!(error = %e, "config reload failed, keeping previous monitors");
+                db::insert_config_reload(&self.pool, reloaded_at, None, Some(&e.to_string()), e.position())
+                    .await
+            }
+        };
+        if let Err(e) = recorded {
+            error!(error = %e, "failed to record config reload");
+        }
+    }
+
+    fn apply_config(&self, config_path: &Path) -> Result<usize, ConfigError> {
+        let mut new_config = config::Config::load(config_path)?;
+        info!(retention_days = new_config.retention_days, "new config parsed");
+        self.set_retention_days(new_config.retention_days);
+        self.set_probe_modules(new_config.resolve_probe_modules());
+        let new_monitors = new_config.resolve();
+        let count = new_monitors.len();
+        info!(count, "new monitors resolved");
+        self.reload(new_monitors);
+        Ok(count)
+    }
+
+    fn set_retention_days(&self, days: u32) {
+        self.retention_days.send_if_modified(|current| {
+            if *current == days {
+                return false;
+            }
+            info!(from = *current, to = days, "retention changed");
+            *current = days;
+            true
+        });
+    }
+
     fn spawn_loop(&self, key: MonitorKey, initial_delay: Duration) {
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        self.shared.triggers.lock().unwrap().insert(key.clone(), command_tx);
         tokio::spawn(run_monitor_loop(
             key,
-            self.monitors.clone(),
+            self.shared.clone(),
+            command_rx,
             self.pool.clone(),
-            self.client.clone(),
-            self.insecure_client.clone(),
+            self.clients.clone(),
             initial_delay,
         ));
     }
@@ -92,10 +287,10 @@ impl MonitorManager {
         let (tx, mut rx) = mpsc::channel::<()>(16);
         let _watcher = {
             let mut w = recommended_watcher(move |res: Result<notify::Event, notify::Error>| {
-                if let Ok(event) = res {
-                    if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_)) {
-                        let _ = tx.blocking_send(());
-                    }
+                if let Ok(event) = res
+                    && matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_) | EventKind::Remove(_))
+                {
+                    let _ = tx.blocking_send(());
                 }
             })
             .expect("failed to create file watcher");
@@ -117,12 +312,7 @@ impl MonitorManager {
                     while rx.try_recv().is_ok() {}
 
                     info!("config.json changed, reloading");
-                    let new_config = config::Config::load(config_path);
-                    info!(retention_days = new_config.retention_days, "new config parsed");
-                    let new_monitors = new_config.resolve();
-                    info!(count = new_monitors.len(), "new monitors resolved");
-                    self.reload(new_monitors);
-                    info!("config reload complete");
+                    self.reload_config(config_path).await;
                 }
             }
         }
@@ -148,12 +338,16 @@ pub fn stagger_delays(monitors: &[ResolvedMonitor]) -> Vec<Duration> {
 
 async fn run_monitor_loop(
     key: MonitorKey,
-    monitors: MonitorMap,
+    shared: Shared,
+    mut commands: mpsc::UnboundedReceiver<Command>,
     pool: PgPool,
-    client: Client,
-    insecure_client: Client,
+    clients: HttpClients,
     initial_delay: Duration,
 ) {
+    // Callers waiting on the next check, from check-now triggers.
+    let mut waiters = Vec::new();
+    let mut lag = None;
+    set_next_run(&shared.runtime, &key, initial_delay);
     if !initial_delay.is_zero() {
         info!(
             project = %key.0,
@@ -161,20 +355,50 @@ async fn run_monitor_loop(
             delay_ms = initial_delay.as_millis() as u64,
             "staggering start"
         );
-        time::sleep(initial_delay).await;
+        lag = wait(initial_delay, &mut commands, &mut waiters, &shared, &key, false).await;
     }
 
+    let mut state = match db::fetch_monitor_state(&pool, &key.0, &key.1).await {
+        Ok(state) => state.unwrap_or_default(),
+        Err(e) => {
+            error!(project = %key.0, site = %key.1, error = %e, "failed to load monitor state");
+            MonitorState::default()
+        }
+    };
+    let mut retries = 0;
+    let mut first_failure: Option<CheckResult> = None;
+    let mut first_slow: Option<CheckResult> = None;
+    let mut was_paused = false;
+
     loop {
-        let Some(monitor) = monitors.lock().unwrap().get(&key).cloned() else {
+        let Some(monitor) = shared.monitors.lock().unwrap().get(&key).cloned() else {
             info!(project = %key.0, site = %key.1, "monitor removed, stopping");
+            shared.runtime.lock().unwrap().remove(&key);
+            shared.triggers.lock().unwrap().remove(&key);
+            shared.metrics.remove(&key);
             return;
         };
 
-        let selected_client = if monitor.tls_skip_verify {
-            &insecure_client
-        } else {
-            &client
-        };
+        let paused = shared.is_paused(&key);
+        if paused {
+            if !was_paused {
+                pause(&pool, &monitor, &shared).await;
+                was_paused = true;
+            }
+            // Check-now callers get no result from a paused monitor.
+            waiters.clear();
+            wait(monitor.interval, &mut commands, &mut waiters, &shared, &key, true).await;
+            continue;
+        }
+        if was_paused {
+            info!(project = %key.0, site = %key.1, "monitor resumed");
+            // Whatever happened while paused is unknown; start over from a clean slate.
+            state = MonitorState::default();
+            retries = 0;
+            first_failure = None;
+            first_slow = None;
+            was_paused = false;
+        }
 
         info!(
             project = monitor.project_id,
@@ -183,18 +407,28 @@ async fn run_monitor_loop(
             "checking"
         );
 
-        let result = monitor::execute_check(selected_client, &monitor).await;
+        let mut result = monitor::execute_check(clients.for_check(&monitor.check), &monitor).await;
+        result.is_retry = retries > 0;
+        result.in_maintenance = maintenance::is_active(&monitor.maintenance, result.checked_at);
 
         info!(
             project = result.project_id,
             site = result.site_key,
             is_up = result.is_up,
+            degraded = result.degraded,
+            is_retry = result.is_retry,
+            in_maintenance = result.in_maintenance,
             status_code = result.status_code,
             response_ms = result.response_ms,
             "check complete"
         );
 
+        if let Some(lag) = lag.take() {
+            shared.metrics.record_lag(&key, lag);
+        }
+
         if let Err(e) = db::insert_check_result(&pool, &result).await {
+            shared.metrics.record_db_insert_failure("monitor_checks");
             error!(
                 project = result.project_id,
                 site = result.site_key,
@@ -202,8 +436,56 @@ async fn run_monitor_loop(
                 "failed to insert check result"
             );
         }
+        if let Some(cert) = &result.tls
+            && let Err(e) = db::upsert_tls_certificate(&pool, &result, cert).await
+        {
+            shared.metrics.record_db_insert_failure("tls_certificates");
+            error!(
+                project = result.project_id,
+                site = result.site_key,
+                error = %e,
+                "failed to store TLS certificate"
+            );
+        }
+
+        // Checks inside a maintenance window are stored but never move the confirmed state,
+        // and neither do checks that finish after the monitor was paused.
+        if !result.in_maintenance && !shared.is_paused(&key) {
+            let transition = state.observe(
+                CheckStatus::of(result.is_up, result.degraded),
+                monitor.failures_before_down,
+                monitor.successes_before_up,
+            );
+            if !result.is_up && state.consecutive_failures == 1 {
+                first_failure = Some(result.clone());
+            }
+            if result.degraded && state.consecutive_slow == 1 {
+                first_slow = Some(result.clone());
+            }
+            let streak_start = match transition {
+                Some(Transition::Degraded) => first_slow.as_ref(),
+                _ => first_failure.as_ref(),
+            };
+            let downtime = match update_incident(&pool, &result, &state, transition, streak_start).await {
+                Ok(downtime) => downtime,
+                Err(e) => {
+                    shared.metrics.record_db_insert_failure("incidents");
+                    error!(
+                        project = result.project_id,
+                        site = result.site_key,
+                        error = %e,
+                        "failed to update incident"
+                    );
+                    None
+                }
+            };
+            if let Some(transition) = transition {
+                alert::dispatch(&pool, clients.shared(), &monitor.notifiers, Alert::new(transition, &result, downtime));
+            }
+        }
 
-        if let Err(e) = db::upsert_monitor_status(&pool, &result).await {
+        if let Err(e) = db::upsert_monitor_status(&pool, &result, &state).await {
+            shared.metrics.record_db_insert_failure("monitor_status");
             error!(
                 project = result.project_id,
                 site = result.site_key,
@@ -212,26 +494,210 @@ async fn run_monitor_loop(
             );
         }
 
-        time::sleep(monitor.interval).await;
+        // Failures during maintenance aren't retried early.
+        let delay = next_delay(&monitor, result.is_up || result.in_maintenance, &mut retries);
+        while let Ok(command) = commands.try_recv() {
+            match command {
+                Command::CheckNow(reply) => waiters.push(reply),
+                // Handled below: a pause that arrived mid-check must not wait out the interval.
+                Command::Wake => {}
+            }
+        }
+        for reply in waiters.drain(..) {
+            let _ = reply.send(result.clone());
+        }
+        shared.metrics.record_check(&result, &state);
+        {
+            let mut runtime = shared.runtime.lock().unwrap();
+            let entry = runtime.entry(key.clone()).or_default();
+            entry.state = state.clone();
+            entry.last_result = Some(result);
+            entry.paused = false;
+        }
+        if shared.is_paused(&key) {
+            continue;
+        }
+        set_next_run(&shared.runtime, &key, delay);
+        lag = wait(delay, &mut commands, &mut waiters, &shared, &key, false).await;
+    }
+}
+
+/// Sleeps for `delay`, cut short by a check-now request, whose reply is queued in `waiters`,
+/// or by the monitor being paused or resumed. Returns how late the timer fired if it ran out.
+async fn wait(
+    delay: Duration,
+    commands: &mut mpsc::UnboundedReceiver<Command>,
+    waiters: &mut Vec<oneshot::Sender<CheckResult>>,
+    shared: &Shared,
+    key: &MonitorKey,
+    paused: bool,
+) -> Option<Duration> {
+    let deadline = time::Instant::now() + delay;
+    loop {
+        tokio::select! {
+            _ = time::sleep_until(deadline) => return Some(deadline.elapsed()),
+            Some(command) = commands.recv() => match command {
+                Command::CheckNow(reply) => {
+                    waiters.push(reply);
+                    return None;
+                }
+                Command::Wake if shared.is_paused(key) != paused => return None,
+                Command::Wake => {}
+            },
+        }
+    }
+}
+
+/// Marks the monitor paused and closes its open incidents; nothing is checked until it resumes.
+async fn pause(pool: &PgPool, monitor: &ResolvedMonitor, shared: &Shared) {
+    let key = monitor.key();
+    info!(project = %key.0, site = %key.1, "monitor paused");
+    if let Some(entry) = shared.runtime.lock().unwrap().get_mut(&key) {
+        entry.paused = true;
+        entry.next_run_at = None;
+    }
+    shared.metrics.record_paused(&key);
+    let now = Utc::now();
+    for kind in [IncidentKind::Down, IncidentKind::Degraded] {
+        if let Err(e) = db::close_incident(pool, kind, &key.0, &key.1, now).await {
+            error!(project = %key.0, site = %key.1, error = %e, "failed to close incident on pause");
+        }
+    }
+    if let Err(e) = db::mark_monitor_paused(pool, monitor, now).await {
+        error!(project = %key.0, site = %key.1, error = %e, "failed to mark monitor paused");
+    }
+}
+
+fn set_next_run(runtime: &RuntimeMap, key: &MonitorKey, delay: Duration) {
+    let next_run_at = Utc::now() + delay;
+    runtime.lock().unwrap().entry(key.clone()).or_default().next_run_at = Some(next_run_at);
+}
+
+/// Keeps the `incidents` table in step with state transitions; returns the outage (or slow
+/// spell) duration when an incident is closed. `streak_start` is the first check of the streak
+/// that caused a `Down` or `Degraded` transition.
+async fn update_incident(
+    pool: &PgPool,
+    result: &CheckResult,
+    state: &MonitorState,
+    transition: Option<Transition>,
+    streak_start: Option<&CheckResult>,
+) -> Result<Option<Duration>, sqlx::Error> {
+    let (project, site) = (&result.project_id, &result.site_key);
+    match transition {
+        Some(Transition::Down) => {
+            warn!(
+                project = result.project_id,
+                site = result.site_key,
+                failures = state.consecutive_failures,
+                "monitor down, opening incident"
+            );
+            // An outage supersedes slowness.
+            db::close_incident(pool, IncidentKind::Degraded, project, site, result.checked_at).await?;
+            db::open_incident(pool, IncidentKind::Down, streak_start.unwrap_or(result), state.consecutive_failures)
+                .await?;
+            Ok(None)
+        }
+        Some(Transition::Up) => {
+            let duration_ms = db::close_incident(pool, IncidentKind::Down, project, site, result.checked_at).await?;
+            info!(
+                project = result.project_id,
+                site = result.site_key,
+                successes = state.consecutive_successes,
+                duration_ms,
+                "monitor recovered, incident closed"
+            );
+            Ok(duration_ms.map(|ms| Duration::from_millis(ms.max(0) as u64)))
+        }
+        Some(Transition::Degraded) => {
+            warn!(
+                project = result.project_id,
+                site = result.site_key,
+                slow_checks = state.consecutive_slow,
+                "monitor degraded, opening incident"
+            );
+            db::open_incident(pool, IncidentKind::Degraded, streak_start.unwrap_or(result), state.consecutive_slow)
+                .await?;
+            Ok(None)
+        }
+        Some(Transition::Restored) => {
+            let duration_ms =
+                db::close_incident(pool, IncidentKind::Degraded, project, site, result.checked_at).await?;
+            info!(
+                project = result.project_id,
+                site = result.site_key,
+                fast_checks = state.consecutive_fast,
+                duration_ms,
+                "monitor no longer degraded, incident closed"
+            );
+            Ok(duration_ms.map(|ms| Duration::from_millis(ms.max(0) as u64)))
+        }
+        None if !state.is_up && !result.is_up => {
+            db::record_incident_failure(pool, IncidentKind::Down, project, site).await?;
+            Ok(None)
+        }
+        None if state.is_degraded && result.degraded => {
+            db::record_incident_failure(pool, IncidentKind::Degraded, project, site).await?;
+            Ok(None)
+        }
+        None => Ok(None),
+    }
+}
+
+/// Delay until the next check. A failed check is retried up to `retry_count` times
+/// at `retry_interval` before the monitor falls back to its regular `interval`.
+fn next_delay(monitor: &ResolvedMonitor, check_up: bool, retries: &mut u32) -> Duration {
+    if !check_up && *retries < monitor.retry_count {
+        *retries += 1;
+        monitor.retry_interval
+    } else {
+        *retries = 0;
+        monitor.interval
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use sqlx::postgres::PgPoolOptions;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer, ResponseTemplate};
 
     fn make_monitor(interval_secs: u64) -> ResolvedMonitor {
-        ResolvedMonitor {
-            project_id: "p".into(),
-            site_key: "s".into(),
-            url: "http://example.com".into(),
-            interval: Duration::from_secs(interval_secs),
-            timeout: Duration::from_secs(10),
-            expected_status_code: 200,
-            http_method: "GET".into(),
-            expected_body: None,
-            tls_skip_verify: false,
-        }
+        let mut monitor = config::test_monitor("http://example.com");
+        monitor.interval = Duration::from_secs(interval_secs);
+        monitor
+    }
+
+    #[test]
+    fn failure_without_retries_waits_full_interval() {
+        let monitor = make_monitor(120);
+        let mut retries = 0;
+        assert_eq!(next_delay(&monitor, false, &mut retries), Duration::from_secs(120));
+        assert_eq!(retries, 0);
+    }
+
+    #[test]
+    fn failures_retry_then_fall_back_to_interval() {
+        let mut monitor = make_monitor(120);
+        monitor.retry_count = 2;
+        let mut retries = 0;
+        assert_eq!(next_delay(&monitor, false, &mut retries), Duration::from_secs(10));
+        assert_eq!(retries, 1);
+        assert_eq!(next_delay(&monitor, false, &mut retries), Duration::from_secs(10));
+        assert_eq!(retries, 2);
+        assert_eq!(next_delay(&monitor, false, &mut retries), Duration::from_secs(120));
+        assert_eq!(retries, 0);
+    }
+
+    #[test]
+    fn successful_retry_resets_to_interval() {
+        let mut monitor = make_monitor(120);
+        monitor.retry_count = 3;
+        let mut retries = 0;
+        next_delay(&monitor, false, &mut retries);
+        assert_eq!(next_delay(&monitor, true, &mut retries), Duration::from_secs(120));
+        assert_eq!(retries, 0);
     }
 
     #[test]
@@ -262,4 +728,38 @@ mod tests {
             Duration::from_secs(40),
         ]);
     }
+
+    #[tokio::test]
+    async fn pause_during_check_takes_effect_right_after_it() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_delay(Duration::from_millis(500)))
+            .mount(&server)
+            .await;
+        let pool = PgPoolOptions::new()
+            .acquire_timeout(Duration::from_millis(100))
+            .connect_lazy("postgres://upmon@127.0.0.1:1/upmon")
+            .unwrap();
+        let (retention_tx, _) = watch::channel(90);
+        let manager = MonitorManager::new(pool, HttpClients::new(Duration::from_secs(5)), retention_tx);
+        let monitor = || config::test_monitor(&server.uri());
+        manager.start_initial(vec![monitor()]);
+
+        while server.received_requests().await.unwrap().is_empty() {
+            time::sleep(Duration::from_millis(20)).await;
+        }
+        manager.reload(vec![ResolvedMonitor { paused: true, ..monitor() }]);
+
+        let mut runtime = MonitorRuntime::default();
+        for _ in 0..50 {
+            runtime = manager.snapshot().remove(0).1;
+            if runtime.paused {
+                break;
+            }
+            time::sleep(Duration::from_millis(100)).await;
+        }
+        assert!(runtime.paused);
+        assert!(runtime.next_run_at.is_none());
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+    }
 }
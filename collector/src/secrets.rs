@@ -0,0 +1,235 @@
+use std::path::{Path, PathBuf};
+
+use serde_json::Value;
+
+use crate::validate::ValidationError;
+
+const REDACTED: &str = "<redacted>";
+
+/// Prefix of a reference whose value isn't a secret, like `${plain:PORT}`.
+const PLAIN_PREFIX: &str = "plain:";
+
+/// Values substituted into a config, kept so they can be scrubbed from anything it writes out.
+#[derive(Default)]
+pub struct Resolved {
+    pub secrets: Vec<String>,
+    pub errors: Vec<ValidationError>,
+}
+
+/// Expands `${VAR}` inside strings and replaces `{"secret_file": "path"}` objects with the
+/// file's contents, minus a trailing newline. Relative paths are tried against each of `dirs` in turn.
+/// Every value is a secret except those of `${plain:VAR}` references, meant for ports, hostnames
+/// and the like that would otherwise be masked wherever they appear.
+pub fn resolve(value: &mut Value, lookup: &impl Fn(&str) -> Option<String>, dirs: &[PathBuf]) -> Resolved {
+    let mut resolved = Resolved::default();
+    walk(value, String::new(), lookup, dirs, &mut resolved);
+    resolved
+}
+
+fn walk(
+    value: &mut Value,
+    path: String,
+    lookup: &impl Fn(&str) -> Option<String>,
+    dirs: &[PathBuf],
+    resolved: &mut Resolved,
+) {
+    let child = |key: &str| if path.is_empty() { key.to_string() } else { format!("{path}.{key}") };
+    match value {
+        Value::String(s) => {
+            if let Some(expanded) = interpolate(s, &path, lookup, resolved) {
+                *s = expanded;
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                walk(item, format!("{path}[{i}]"), lookup, dirs, resolved);
+            }
+        }
+        Value::Object(map) => {
+            if map.len() == 1
+                && let Some(Value::String(file)) = map.get("secret_file")
+            {
+                match read_secret_file(file, dirs) {
+                    Ok(contents) => {
+                        let secret = contents.trim_end_matches(['\r', '\n']).to_string();
+                        resolved.secrets.push(secret.clone());
+                        *value = Value::String(secret);
+                    }
+                    Err(e) => resolved.errors.push(ValidationError {
+                        path,
+                        message: format!("failed to read secret_file {file:?}: {e}"),
+                    }),
+                }
+                return;
+            }
+            for (key, item) in map.iter_mut() {
+                walk(item, child(key), lookup, dirs, resolved);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn read_secret_file(file: &str, dirs: &[PathBuf]) -> std::io::Result<String> {
+    let path = Path::new(file);
+    if path.is_absolute() {
+        return std::fs::read_to_string(path);
+    }
+    let mut first_error = None;
+    for dir in dirs {
+        match std::fs::read_to_string(dir.join(path)) {
+            Ok(contents) => return Ok(contents),
+            Err(e) => {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    Err(first_error.unwrap_or_else(|| std::io::ErrorKind::NotFound.into()))
+}
+
+/// `None` when `s` holds no references.
+fn interpolate(
+    s: &str,
+    path: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+    resolved: &mut Resolved,
+) -> Option<String> {
+    if !s.contains("${") {
+        return None;
+    }
+    let mut out = String::new();
+    let mut rest = s;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            out.push_str("${");
+            rest = after;
+            continue;
+        };
+        let (name, secret) = match after[..end].strip_prefix(PLAIN_PREFIX) {
+            Some(name) => (name, false),
+            None => (&after[..end], true),
+        };
+        if !is_var_name(name) {
+            out.push_str("${");
+            rest = after;
+            continue;
+        }
+        match lookup(name) {
+            Some(value) => {
+                out.push_str(&value);
+                if secret {
+                    resolved.secrets.push(value);
+                }
+            }
+            None => resolved.errors.push(ValidationError {
+                path: path.to_string(),
+                message: format!("environment variable {name} is not set"),
+            }),
+        }
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Some(out)
+}
+
+fn is_var_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Replaces every occurrence of each secret in `text`, longest first so overlapping values don't leave fragments.
+pub fn redact(text: &str, secrets: &[String]) -> String {
+    let mut secrets: Vec<&String> = secrets.iter().filter(|s| !s.is_empty()).collect();
+    secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    secrets
+        .into_iter()
+        .fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+}
+
+/// `redact` applied to every string in `value`.
+pub fn redact_value(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::String(s) => *s = redact(s, secrets),
+        Value::Array(items) => items.iter_mut().for_each(|item| redact_value(item, secrets)),
+        Value::Object(map) => map.values_mut().for_each(|item| redact_value(item, secrets)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn env(name: &str) -> Option<String> {
+        match name {
+            "API_TOKEN" => Some("s3cr3t".into()),
+            "HOST" => Some("api.internal".into()),
+            "PORT" => Some("443".into()),
+            "ENV" => Some("prod".into()),
+            "PIN" => Some("42".into()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn interpolates_env_references() {
+        let mut value = json!({
+            "url": "https://${HOST}/health?token=${API_TOKEN}",
+            "headers": { "X-Price": "$5 ${not a var}" }
+        });
+        let resolved = resolve(&mut value, &env, &[]);
+        assert!(resolved.errors.is_empty());
+        assert_eq!(value["url"], "https://api.internal/health?token=s3cr3t");
+        assert_eq!(value["headers"]["X-Price"], "$5 ${not a var}");
+        assert_eq!(resolved.secrets, vec!["api.internal", "s3cr3t"]);
+    }
+
+    #[test]
+    fn plain_references_are_not_secrets() {
+        let mut value = json!({ "url": "https://${HOST}:${plain:PORT}/${plain:ENV}/health?pin=${PIN}&x=${plain:}" });
+        let resolved = resolve(&mut value, &env, &[]);
+        assert!(resolved.errors.is_empty());
+        assert_eq!(value["url"], "https://api.internal:443/prod/health?pin=42&x=${plain:}");
+        assert_eq!(resolved.secrets, vec!["api.internal", "42"]);
+        assert_eq!(
+            redact("status 443 in prod", &resolved.secrets),
+            "status 443 in prod"
+        );
+    }
+
+    #[test]
+    fn reads_secret_files() {
+        let path = std::env::temp_dir().join(format!("upmon-secret-{}", std::process::id()));
+        std::fs::write(&path, "file-token\n").unwrap();
+        let mut value = json!({
+            "projects": [{ "monitors": [{ "headers": { "Authorization": { "secret_file": path } } }] }]
+        });
+        let resolved = resolve(&mut value, &env, &[]);
+        std::fs::remove_file(&path).unwrap();
+        assert!(resolved.errors.is_empty());
+        assert_eq!(value["projects"][0]["monitors"][0]["headers"]["Authorization"], "file-token");
+        assert_eq!(resolved.secrets, vec!["file-token"]);
+    }
+
+    #[test]
+    fn reports_unresolved_references() {
+        let mut value = json!({
+            "notifiers": [{ "url": "https://hooks.example.com/${MISSING}" }],
+            "smtp": { "password": { "secret_file": "/nonexistent/smtp-password" } }
+        });
+        let errors: Vec<String> = resolve(&mut value, &env, &[]).errors.iter().map(|e| e.to_string()).collect();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0], "notifiers[0].url: environment variable MISSING is not set");
+        assert!(errors[1].starts_with("smtp.password: failed to read secret_file \"/nonexistent/smtp-password\""));
+    }
+
+    #[test]
+    fn redact_replaces_longest_first() {
+        let secrets = vec!["abc".to_string(), "abcdef".to_string(), String::new()];
+        assert_eq!(redact("token=abcdef&x=abc", &secrets), "token=<redacted>&x=<redacted>");
+    }
+}
@@ -0,0 +1,183 @@
+/// Confirmed state of one monitor, derived from its stream of raw checks.
+///
+/// A single failed check doesn't flip the state: it takes `failures_before_down`
+/// consecutive failures to go down and `successes_before_up` consecutive successes
+/// to come back up. Slowness uses the same thresholds: `failures_before_down` slow
+/// checks in a row make an up monitor degraded, `successes_before_up` fast ones restore it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorState {
+    pub is_up: bool,
+    pub is_degraded: bool,
+    pub consecutive_failures: u32,
+    pub consecutive_successes: u32,
+    pub consecutive_slow: u32,
+    pub consecutive_fast: u32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transition {
+    Down,
+    Up,
+    Degraded,
+    Restored,
+}
+
+/// Outcome of a single check as far as the state machine cares.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CheckStatus {
+    Up,
+    Slow,
+    Down,
+}
+
+impl CheckStatus {
+    pub fn of(is_up: bool, degraded: bool) -> Self {
+        match (is_up, degraded) {
+            (false, _) => CheckStatus::Down,
+            (true, true) => CheckStatus::Slow,
+            (true, false) => CheckStatus::Up,
+        }
+    }
+}
+
+impl Default for MonitorState {
+    fn default() -> Self {
+        Self {
+            is_up: true,
+            is_degraded: false,
+            consecutive_failures: 0,
+            consecutive_successes: 0,
+            consecutive_slow: 0,
+            consecutive_fast: 0,
+        }
+    }
+}
+
+impl MonitorState {
+    pub fn observe(
+        &mut self,
+        check: CheckStatus,
+        failures_before_down: u32,
+        successes_before_up: u32,
+    ) -> Option<Transition> {
+        if check == CheckStatus::Down {
+            self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+            self.consecutive_successes = 0;
+            self.consecutive_slow = 0;
+            self.consecutive_fast = 0;
+            if self.is_up && self.consecutive_failures >= failures_before_down {
+                self.is_up = false;
+                self.is_degraded = false;
+                return Some(Transition::Down);
+            }
+            return None;
+        }
+
+        self.consecutive_successes = self.consecutive_successes.saturating_add(1);
+        self.consecutive_failures = 0;
+        if check == CheckStatus::Slow {
+            self.consecutive_slow = self.consecutive_slow.saturating_add(1);
+            self.consecutive_fast = 0;
+        } else {
+            self.consecutive_fast = self.consecutive_fast.saturating_add(1);
+            self.consecutive_slow = 0;
+        }
+
+        // A recovering monitor comes back up first; slowness is judged from there.
+        if !self.is_up {
+            if self.consecutive_successes >= successes_before_up {
+                self.is_up = true;
+                return Some(Transition::Up);
+            }
+        } else if !self.is_degraded && self.consecutive_slow >= failures_before_down {
+            self.is_degraded = true;
+            return Some(Transition::Degraded);
+        } else if self.is_degraded && self.consecutive_fast >= successes_before_up {
+            self.is_degraded = false;
+            return Some(Transition::Restored);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_failure_goes_down_with_threshold_one() {
+        let mut state = MonitorState::default();
+        assert_eq!(state.observe(CheckStatus::Down, 1, 1), Some(Transition::Down));
+        assert!(!state.is_up);
+    }
+
+    #[test]
+    fn failures_below_threshold_stay_up() {
+        let mut state = MonitorState::default();
+        assert_eq!(state.observe(CheckStatus::Down, 3, 1), None);
+        assert_eq!(state.observe(CheckStatus::Down, 3, 1), None);
+        assert!(state.is_up);
+        assert_eq!(state.consecutive_failures, 2);
+        assert_eq!(state.observe(CheckStatus::Down, 3, 1), Some(Transition::Down));
+        assert!(!state.is_up);
+    }
+
+    #[test]
+    fn success_resets_failure_streak() {
+        let mut state = MonitorState::default();
+        state.observe(CheckStatus::Down, 2, 1);
+        state.observe(CheckStatus::Up, 2, 1);
+        assert_eq!(state.observe(CheckStatus::Down, 2, 1), None);
+        assert!(state.is_up);
+    }
+
+    #[test]
+    fn recovery_needs_consecutive_successes() {
+        let mut state = MonitorState::default();
+        state.observe(CheckStatus::Down, 1, 2);
+        assert_eq!(state.observe(CheckStatus::Up, 1, 2), None);
+        assert!(!state.is_up);
+        assert_eq!(state.observe(CheckStatus::Down, 1, 2), None);
+        assert_eq!(state.observe(CheckStatus::Up, 1, 2), None);
+        assert_eq!(state.observe(CheckStatus::Up, 1, 2), Some(Transition::Up));
+        assert!(state.is_up);
+    }
+
+    #[test]
+    fn repeated_failures_while_down_emit_nothing() {
+        let mut state = MonitorState::default();
+        state.observe(CheckStatus::Down, 1, 1);
+        assert_eq!(state.observe(CheckStatus::Down, 1, 1), None);
+        assert_eq!(state.consecutive_failures, 2);
+    }
+
+    #[test]
+    fn slow_checks_degrade_and_fast_checks_restore() {
+        let mut state = MonitorState::default();
+        assert_eq!(state.observe(CheckStatus::Slow, 2, 2), None);
+        assert_eq!(state.observe(CheckStatus::Slow, 2, 2), Some(Transition::Degraded));
+        assert!(state.is_up && state.is_degraded);
+        assert_eq!(state.observe(CheckStatus::Slow, 2, 2), None);
+        assert_eq!(state.observe(CheckStatus::Up, 2, 2), None);
+        assert_eq!(state.observe(CheckStatus::Up, 2, 2), Some(Transition::Restored));
+        assert!(!state.is_degraded);
+    }
+
+    #[test]
+    fn slow_checks_count_toward_recovery() {
+        let mut state = MonitorState::default();
+        state.observe(CheckStatus::Down, 1, 1);
+        assert_eq!(state.observe(CheckStatus::Slow, 1, 1), Some(Transition::Up));
+        assert!(state.is_up && !state.is_degraded);
+        assert_eq!(state.observe(CheckStatus::Slow, 1, 1), Some(Transition::Degraded));
+    }
+
+    #[test]
+    fn going_down_clears_degraded() {
+        let mut state = MonitorState::default();
+        state.observe(CheckStatus::Slow, 1, 1);
+        assert_eq!(state.observe(CheckStatus::Down, 1, 1), Some(Transition::Down));
+        assert!(!state.is_degraded);
+        assert_eq!(state.consecutive_slow, 0);
+    }
+}
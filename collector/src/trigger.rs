@@ -0,0 +1,123 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde_json::json;
+use sqlx::PgPool;
+use sqlx::postgres::PgListener;
+use tokio::time;
+use tracing::{error, info};
+
+use crate::config::MonitorKey;
+use crate::scheduler::MonitorManager;
+
+/// `NOTIFY upmon_check_now, '<scope>'` runs an immediate check; see `Scope::parse` for the payload.
+pub const CHECK_NOW_CHANNEL: &str = "upmon_check_now";
+/// Each check run for a `NOTIFY` is announced here as a JSON summary.
+pub const CHECK_DONE_CHANNEL: &str = "upmon_check_done";
+
+/// Which monitors a check-now request covers.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Scope {
+    All,
+    Project(String),
+    Monitor(MonitorKey),
+}
+
+impl Scope {
+    /// `""` or `*` for everything, `project` for one project, `project/site` for one monitor.
+    pub fn parse(payload: &str) -> Self {
+        match payload.trim() {
+            "" | "*" => Scope::All,
+            payload => match payload.split_once('/') {
+                Some((project_id, site_key)) => Scope::Monitor((project_id.to_string(), site_key.to_string())),
+                None => Scope::Project(payload.to_string()),
+            },
+        }
+    }
+
+    pub fn matches(&self, key: &MonitorKey) -> bool {
+        match self {
+            Scope::All => true,
+            Scope::Project(project_id) => key.0 == *project_id,
+            Scope::Monitor(monitor) => key == monitor,
+        }
+    }
+}
+
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Scope::All => f.write_str("*"),
+            Scope::Project(project_id) => f.write_str(project_id),
+            Scope::Monitor((project_id, site_key)) => write!(f, "{project_id}/{site_key}"),
+        }
+    }
+}
+
+/// Listens on `CHECK_NOW_CHANNEL` for as long as the collector runs, reconnecting after errors.
+pub async fn listen(pool: PgPool, manager: Arc<MonitorManager>) {
+    loop {
+        if let Err(e) = listen_once(&pool, &manager).await {
+            error!(error = %e, channel = CHECK_NOW_CHANNEL, "check-now listener failed, retrying");
+        }
+        time::sleep(Duration::from_secs(5)).await;
+    }
+}
+
+async fn listen_once(pool: &PgPool, manager: &Arc<MonitorManager>) -> Result<(), sqlx::Error> {
+    let mut listener = PgListener::connect_with(pool).await?;
+    listener.listen(CHECK_NOW_CHANNEL).await?;
+    info!(channel = CHECK_NOW_CHANNEL, "listening for check-now requests");
+    loop {
+        let notification = listener.recv().await?;
+        let scope = Scope::parse(notification.payload());
+        info!(%scope, "check now requested via notify");
+        let (pool, manager) = (pool.clone(), manager.clone());
+        tokio::spawn(async move {
+            for result in manager.check_now(&scope).await {
+                let summary = json!({
+                    "project_id": result.project_id,
+                    "site_key": result.site_key,
+                    "checked_at": result.checked_at,
+                    "is_up": result.is_up,
+                    "degraded": result.degraded,
+                    "status_code": result.status_code,
+                    "response_ms": result.response_ms,
+                    "error_type": result.error_type.as_ref().map(|e| e.as_str()),
+                });
+                let sent = sqlx::query("SELECT pg_notify($1, $2)")
+                    .bind(CHECK_DONE_CHANNEL)
+                    .bind(summary.to_string())
+                    .execute(&pool)
+                    .await;
+                if let Err(e) = sent {
+                    error!(project = result.project_id, site = result.site_key, error = %e, "failed to announce check result");
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_payloads() {
+        assert_eq!(Scope::parse(""), Scope::All);
+        assert_eq!(Scope::parse(" * "), Scope::All);
+        assert_eq!(Scope::parse("abubot"), Scope::Project("abubot".into()));
+        assert_eq!(Scope::parse("abubot/prod"), Scope::Monitor(("abubot".into(), "prod".into())));
+    }
+
+    #[test]
+    fn matches_keys() {
+        let key = ("abubot".to_string(), "prod".to_string());
+        assert!(Scope::All.matches(&key));
+        assert!(Scope::parse("abubot").matches(&key));
+        assert!(!Scope::parse("other").matches(&key));
+        assert!(Scope::parse("abubot/prod").matches(&key));
+        assert!(!Scope::parse("abubot/staging").matches(&key));
+    }
+}
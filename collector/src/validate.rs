@@ -0,0 +1,566 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::net::IpAddr;
+
+use lettre::message::Mailbox;
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+
+use crate::config::{CheckType, Config, DnsRecordType, Monitor, NotifierConfig, NotifierKind, parse_resolver};
+use crate::maintenance::MaintenanceWindow;
+
+pub struct ValidationError {
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Semantic checks serde can't express. Every problem is collected, not just the first.
+pub fn validate(config: &Config) -> Vec<ValidationError> {
+    let mut errors = Vec::new();
+    let mut push = |path: String, message: String| errors.push(ValidationError { path, message });
+
+    if config.retention_days == 0 {
+        push("retention_days".into(), "must be greater than 0".into());
+    }
+    if config.defaults.interval_sec == 0 {
+        push("defaults.interval_sec".into(), "must be greater than 0".into());
+    }
+    if config.defaults.timeout_sec == 0 {
+        push("defaults.timeout_sec".into(), "must be greater than 0".into());
+    }
+    if let Some(message) = check_method(&config.defaults.http_method) {
+        push("defaults.http_method".into(), message);
+    }
+    if config.defaults.failures_before_down == 0 {
+        push("defaults.failures_before_down".into(), "must be greater than 0".into());
+    }
+    if config.defaults.successes_before_up == 0 {
+        push("defaults.successes_before_up".into(), "must be greater than 0".into());
+    }
+    if config.defaults.retry_interval_sec == 0 {
+        push("defaults.retry_interval_sec".into(), "must be greater than 0".into());
+    }
+    check_headers(&mut push, "defaults.headers", &config.defaults.headers);
+    if config.defaults.cert_min_days > config.defaults.cert_warn_days {
+        push("defaults.cert_min_days".into(), "must not exceed cert_warn_days".into());
+    }
+    check_response_thresholds(
+        &mut push,
+        "defaults",
+        config.defaults.response_warn_ms,
+        config.defaults.response_critical_ms,
+    );
+
+    if let Some(smtp) = &config.smtp
+        && let Err(e) = smtp.from.parse::<Mailbox>()
+    {
+        push("smtp.from".into(), format!("invalid address: {e}"));
+    }
+    check_notifiers(&mut push, config, "notifiers", &config.notifiers);
+    check_maintenance(&mut push, "maintenance", &config.maintenance);
+
+    let mut seen: HashMap<(&str, &str), String> = HashMap::new();
+    for (pi, project) in config.projects.iter().enumerate() {
+        let project_path = format!("projects[{pi}]");
+        if project.id.is_empty() {
+            push(format!("{project_path}.id"), "must not be empty".into());
+        }
+        if let Some(notifiers) = &project.notifiers {
+            check_notifiers(&mut push, config, &format!("{project_path}.notifiers"), notifiers);
+        }
+        check_maintenance(&mut push, &format!("{project_path}.maintenance"), &project.maintenance);
+
+        for (mi, monitor) in project.monitors.iter().enumerate() {
+            let path = format!("{project_path}.monitors[{mi}]");
+            if monitor.site_key.is_empty() {
+                push(format!("{path}.site_key"), "must not be empty".into());
+            }
+            if let Some(first) = seen.insert((&project.id, &monitor.site_key), path.clone()) {
+                push(
+                    format!("{path}.site_key"),
+                    format!("duplicate monitor {}/{} (first defined at {first})", project.id, monitor.site_key),
+                );
+            }
+            match monitor.check_type {
+                CheckType::Http => match &monitor.url {
+                    Some(url) => {
+                        if let Some(message) = check_url(url) {
+                            push(format!("{path}.url"), message);
+                        }
+                    }
+                    None => push(format!("{path}.url"), "required for http monitors".into()),
+                },
+                CheckType::Tcp => {
+                    if monitor.host.as_deref().is_none_or(str::is_empty) {
+                        push(format!("{path}.host"), "required for tcp monitors".into());
+                    }
+                    if monitor.port.is_none() {
+                        push(format!("{path}.port"), "required for tcp monitors".into());
+                    }
+                }
+                CheckType::Icmp => {
+                    if monitor.host.as_deref().is_none_or(str::is_empty) {
+                        push(format!("{path}.host"), "required for icmp monitors".into());
+                    }
+                }
+                CheckType::Dns => {
+                    if monitor.host.as_deref().is_none_or(str::is_empty) {
+                        push(format!("{path}.host"), "required for dns monitors".into());
+                    }
+                }
+                CheckType::Tls => {
+                    if monitor.host.as_deref().is_none_or(str::is_empty) {
+                        push(format!("{path}.host"), "required for tls monitors".into());
+                    }
+                }
+            }
+            check_monitor_settings(&mut push, config, &path, monitor);
+            check_maintenance(&mut push, &format!("{path}.maintenance"), &monitor.maintenance);
+        }
+    }
+
+    for (name, module) in &config.probe_modules {
+        let path = format!("probe_modules.{name}");
+        if module.url.is_some() {
+            push(format!("{path}.url"), "not allowed, the probe's target sets it".into());
+        }
+        if module.host.is_some() {
+            push(format!("{path}.host"), "not allowed, the probe's target sets it".into());
+        }
+        check_monitor_settings(&mut push, config, &path, module);
+    }
+
+    errors
+}
+
+/// Checks shared by monitors and probe modules, i.e. everything but the target.
+fn check_monitor_settings(push: &mut impl FnMut(String, String), config: &Config, path: &str, monitor: &Monitor) {
+    match monitor.check_type {
+        CheckType::Http => {}
+        CheckType::Tcp | CheckType::Tls => {
+            if monitor.port == Some(0) {
+                push(format!("{path}.port"), "must be greater than 0".into());
+            }
+        }
+        CheckType::Icmp => {
+            if monitor.ping_count.is_some_and(|n| !(1..=100).contains(&n)) {
+                push(format!("{path}.ping_count"), "must be between 1 and 100".into());
+            }
+            if monitor.max_packet_loss_pct.is_some_and(|p| !(0.0..=100.0).contains(&p)) {
+                push(format!("{path}.max_packet_loss_pct"), "must be between 0 and 100".into());
+            }
+        }
+        CheckType::Dns => {
+            if let Some(resolver) = &monitor.resolver
+                && parse_resolver(resolver).is_none()
+            {
+                push(format!("{path}.resolver"), format!("invalid resolver address {resolver:?}, expected ip or ip:port"));
+            }
+            if matches!(monitor.record_type, None | Some(DnsRecordType::A | DnsRecordType::Aaaa)) {
+                for (j, answer) in monitor.expected_answers.iter().flatten().enumerate() {
+                    if answer.parse::<IpAddr>().is_err() {
+                        push(format!("{path}.expected_answers[{j}]"), format!("{answer:?} is not an IP address"));
+                    }
+                }
+            }
+        }
+    }
+    if monitor.interval_sec == Some(0) {
+        push(format!("{path}.interval_sec"), "must be greater than 0".into());
+    }
+    if monitor.timeout_sec == Some(0) {
+        push(format!("{path}.timeout_sec"), "must be greater than 0".into());
+    }
+    if let Some(message) = monitor.http_method.as_deref().and_then(check_method) {
+        push(format!("{path}.http_method"), message);
+    }
+    for (ai, assertion) in monitor.assertions.iter().flatten().enumerate() {
+        if let Some(message) = assertion.check() {
+            push(format!("{path}.assertions[{ai}]"), message);
+        }
+    }
+    if let Some(headers) = &monitor.headers {
+        check_headers(push, &format!("{path}.headers"), headers);
+    }
+    if monitor.failures_before_down == Some(0) {
+        push(format!("{path}.failures_before_down"), "must be greater than 0".into());
+    }
+    if monitor.successes_before_up == Some(0) {
+        push(format!("{path}.successes_before_up"), "must be greater than 0".into());
+    }
+    if monitor.retry_interval_sec == Some(0) {
+        push(format!("{path}.retry_interval_sec"), "must be greater than 0".into());
+    }
+    if monitor.cert_min_days.is_some() || monitor.cert_warn_days.is_some() {
+        let min_days = monitor.cert_min_days.unwrap_or(config.defaults.cert_min_days);
+        let warn_days = monitor.cert_warn_days.unwrap_or(config.defaults.cert_warn_days);
+        if min_days > warn_days {
+            push(format!("{path}.cert_min_days"), format!("must not exceed cert_warn_days ({warn_days})"));
+        }
+    }
+    if monitor.response_warn_ms.is_some() || monitor.response_critical_ms.is_some() {
+        check_response_thresholds(
+            push,
+            path,
+            monitor.response_warn_ms.or(config.defaults.response_warn_ms),
+            monitor.response_critical_ms.or(config.defaults.response_critical_ms),
+        );
+    }
+}
+
+fn check_response_thresholds(
+    push: &mut impl FnMut(String, String),
+    path: &str,
+    warn_ms: Option<u32>,
+    critical_ms: Option<u32>,
+) {
+    if warn_ms == Some(0) {
+        push(format!("{path}.response_warn_ms"), "must be greater than 0".into());
+    }
+    if critical_ms == Some(0) {
+        push(format!("{path}.response_critical_ms"), "must be greater than 0".into());
+    }
+    if let (Some(warn_ms), Some(critical_ms)) = (warn_ms, critical_ms)
+        && critical_ms > 0
+        && warn_ms >= critical_ms
+    {
+        push(format!("{path}.response_warn_ms"), format!("must be less than response_critical_ms ({critical_ms})"));
+    }
+}
+
+fn check_notifiers(
+    push: &mut impl FnMut(String, String),
+    config: &Config,
+    path: &str,
+    notifiers: &[NotifierConfig],
+) {
+    for (i, notifier) in notifiers.iter().enumerate() {
+        let path = format!("{path}[{i}]");
+        if notifier.max_attempts == 0 {
+            push(format!("{path}.max_attempts"), "must be greater than 0".into());
+        }
+        match &notifier.kind {
+            NotifierKind::Webhook(webhook) => {
+                if let Some(message) = check_url(&webhook.url) {
+                    push(format!("{path}.url"), message);
+                }
+            }
+            NotifierKind::Email(email) => {
+                if config.smtp.is_none() {
+                    push(path.clone(), "email notifier requires a top-level smtp section".into());
+                }
+                if email.to.is_empty() {
+                    push(format!("{path}.to"), "must not be empty".into());
+                }
+                for (ti, to) in email.to.iter().enumerate() {
+                    if let Err(e) = to.parse::<Mailbox>() {
+                        push(format!("{path}.to[{ti}]"), format!("invalid address: {e}"));
+                    }
+                }
+            }
+            NotifierKind::Slack(slack) => {
+                if let Some(message) = check_url(&slack.webhook_url) {
+                    push(format!("{path}.webhook_url"), message);
+                }
+            }
+            NotifierKind::Discord(discord) => {
+                if let Some(message) = check_url(&discord.webhook_url) {
+                    push(format!("{path}.webhook_url"), message);
+                }
+            }
+            NotifierKind::Telegram(telegram) => {
+                if let Some(message) = check_url(&telegram.api_url) {
+                    push(format!("{path}.api_url"), message);
+                }
+                if telegram.bot_token.is_empty() {
+                    push(format!("{path}.bot_token"), "must not be empty".into());
+                }
+                if telegram.chat_id.is_empty() {
+                    push(format!("{path}.chat_id"), "must not be empty".into());
+                }
+            }
+            NotifierKind::Matrix(matrix) => {
+                if let Some(message) = check_url(&matrix.homeserver) {
+                    push(format!("{path}.homeserver"), message);
+                }
+                if matrix.access_token.is_empty() {
+                    push(format!("{path}.access_token"), "must not be empty".into());
+                }
+                if matrix.room_id.is_empty() {
+                    push(format!("{path}.room_id"), "must not be empty".into());
+                }
+            }
+        }
+    }
+}
+
+fn check_maintenance(push: &mut impl FnMut(String, String), path: &str, windows: &[MaintenanceWindow]) {
+    for (i, window) in windows.iter().enumerate() {
+        let path = format!("{path}[{i}]");
+        match window {
+            MaintenanceWindow::Once { start, end } => {
+                if start >= end {
+                    push(format!("{path}.end"), "must be after start".into());
+                }
+            }
+            MaintenanceWindow::Recurring { cron, duration_min } => {
+                if let Err(e) = cron.schedule() {
+                    push(format!("{path}.cron"), format!("invalid cron expression: {e}"));
+                }
+                if *duration_min == 0 {
+                    push(format!("{path}.duration_min"), "must be greater than 0".into());
+                }
+            }
+        }
+    }
+}
+
+fn check_headers(push: &mut impl FnMut(String, String), path: &str, headers: &BTreeMap<String, String>) {
+    for (name, value) in headers {
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            push(format!("{path}.{name}"), "invalid header name".into());
+        } else if HeaderValue::from_str(value).is_err() {
+            // The value itself may be a credential, so it stays out of the message.
+            push(format!("{path}.{name}"), "invalid header value".into());
+        }
+    }
+}
+
+pub fn check_url(url: &str) -> Option<String> {
+    match Url::parse(url) {
+        Ok(u) if matches!(u.scheme(), "http" | "https") => None,
+        Ok(u) => Some(format!("unsupported scheme '{}'", u.scheme())),
+        Err(e) => Some(format!("invalid URL: {e}")),
+    }
+}
+
+fn check_method(method: &str) -> Option<String> {
+    method
+        .parse::<Method>()
+        .err()
+        .map(|_| format!("invalid HTTP method '{method}'"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn errors_for(json: &str) -> Vec<String> {
+        let config: Config = serde_json::from_str(json).unwrap();
+        validate(&config).iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn valid_config_has_no_errors() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "a", "url": "https://example.com/health" },
+                    { "site_key": "b", "url": "http://example.com", "http_method": "HEAD" }
+                ]
+            }]
+        }"#);
+        assert!(errors.is_empty(), "{errors:?}");
+    }
+
+    #[test]
+    fn duplicate_monitor_reports_both_paths() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [
+                { "id": "proj1", "monitors": [{ "site_key": "a", "url": "http://example.com" }] },
+                { "id": "proj1", "monitors": [{ "site_key": "a", "url": "http://example.org" }] }
+            ]
+        }"#);
+        assert_eq!(errors, vec![
+            "projects[1].monitors[0].site_key: duplicate monitor proj1/a (first defined at projects[0].monitors[0])",
+        ]);
+    }
+
+    #[test]
+    fn invalid_notifiers_are_reported() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "notifiers": [{ "type": "webhook", "url": "hooks.example.com" }],
+            "projects": [{
+                "id": "proj1",
+                "notifiers": [{ "type": "webhook", "url": "https://hooks.example.com", "max_attempts": 0 }],
+                "monitors": []
+            }]
+        }"#);
+        assert_eq!(errors, vec![
+            "notifiers[0].url: invalid URL: relative URL without a base",
+            "projects[0].notifiers[0].max_attempts: must be greater than 0",
+        ]);
+    }
+
+    #[test]
+    fn email_notifier_checks() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "notifiers": [
+                { "type": "email", "to": [] },
+                { "type": "email", "to": ["ops@example.com", "not an address"] }
+            ],
+            "projects": []
+        }"#);
+        assert_eq!(errors, vec![
+            "notifiers[0]: email notifier requires a top-level smtp section",
+            "notifiers[0].to: must not be empty",
+            "notifiers[1]: email notifier requires a top-level smtp section",
+            "notifiers[1].to[1]: invalid address: Invalid input",
+        ]);
+    }
+
+    #[test]
+    fn chat_notifier_checks() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "notifiers": [
+                { "type": "slack", "webhook_url": "https://hooks.slack.com/services/x" },
+                { "type": "discord", "webhook_url": "discord" },
+                { "type": "telegram", "bot_token": "", "chat_id": "42", "api_url": "localhost" },
+                { "type": "matrix", "homeserver": "https://matrix.example.org", "access_token": "t", "room_id": "" }
+            ],
+            "projects": []
+        }"#);
+        assert_eq!(errors, vec![
+            "notifiers[1].webhook_url: invalid URL: relative URL without a base",
+            "notifiers[2].api_url: invalid URL: relative URL without a base",
+            "notifiers[2].bot_token: must not be empty",
+            "notifiers[3].room_id: must not be empty",
+        ]);
+    }
+
+    #[test]
+    fn maintenance_window_checks() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "maintenance": [{ "start": "2026-03-01T02:00:00Z", "end": "2026-03-01T01:00:00Z" }],
+            "projects": [{
+                "id": "proj1",
+                "monitors": [{
+                    "site_key": "a",
+                    "url": "http://example.com",
+                    "maintenance": [{ "cron": "0 25 * * *", "duration_min": 0 }]
+                }]
+            }]
+        }"#);
+        assert_eq!(errors, vec![
+            "maintenance[0].end: must be after start",
+            "projects[0].monitors[0].maintenance[0].cron: invalid cron expression: value 25 out of range 0-23",
+            "projects[0].monitors[0].maintenance[0].duration_min: must be greater than 0",
+        ]);
+    }
+
+    #[test]
+    fn target_fields_depend_on_type() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [{
+                "id": "proj1",
+                "monitors": [
+                    { "site_key": "a" },
+                    { "site_key": "b", "type": "tcp", "url": "http://example.com" },
+                    { "site_key": "c", "type": "tcp", "host": "db.internal", "port": 0 },
+                    { "site_key": "d", "type": "tcp", "host": "db.internal", "port": 5432 },
+                    { "site_key": "e", "type": "icmp" },
+                    { "site_key": "f", "type": "icmp", "host": "gw", "ping_count": 0, "max_packet_loss_pct": 101 },
+                    { "site_key": "g", "type": "icmp", "host": "gw", "ping_count": 10, "max_packet_loss_pct": 0 },
+                    { "site_key": "h", "type": "dns", "resolver": "dns.google", "expected_answers": ["example.com"] },
+                    { "site_key": "i", "type": "dns", "host": "example.com", "record_type": "CNAME", "resolver": "[::1]:5353",
+                      "expected_answers": ["example.net"] },
+                    { "site_key": "j", "type": "tls", "port": 0 },
+                    { "site_key": "k", "type": "tls", "host": "example.com", "cert_min_days": 20 }
+                ]
+            }]
+        }"#);
+        assert_eq!(errors, vec![
+            "projects[0].monitors[0].url: required for http monitors",
+            "projects[0].monitors[1].host: required for tcp monitors",
+            "projects[0].monitors[1].port: required for tcp monitors",
+            "projects[0].monitors[2].port: must be greater than 0",
+            "projects[0].monitors[4].host: required for icmp monitors",
+            "projects[0].monitors[5].ping_count: must be between 1 and 100",
+            "projects[0].monitors[5].max_packet_loss_pct: must be between 0 and 100",
+            "projects[0].monitors[7].host: required for dns monitors",
+            "projects[0].monitors[7].resolver: invalid resolver address \"dns.google\", expected ip or ip:port",
+            "projects[0].monitors[7].expected_answers[0]: \"example.com\" is not an IP address",
+            "projects[0].monitors[9].host: required for tls monitors",
+            "projects[0].monitors[9].port: must be greater than 0",
+            "projects[0].monitors[10].cert_min_days: must not exceed cert_warn_days (14)",
+        ]);
+    }
+
+    #[test]
+    fn probe_modules_take_no_target() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 60, "timeout_sec": 10 },
+            "projects": [],
+            "probe_modules": {
+                "api": { "url": "https://example.com", "timeout_sec": 0 },
+                "smtp": { "type": "tcp", "port": 25 },
+                "ping": { "type": "icmp", "host": "gw", "ping_count": 0 }
+            }
+        }"#);
+        assert_eq!(errors, vec![
+            "probe_modules.api.url: not allowed, the probe's target sets it",
+            "probe_modules.api.timeout_sec: must be greater than 0",
+            "probe_modules.ping.host: not allowed, the probe's target sets it",
+            "probe_modules.ping.ping_count: must be between 1 and 100",
+        ]);
+    }
+
+    #[test]
+    fn collects_every_problem() {
+        let errors = errors_for(r#"{
+            "defaults": { "interval_sec": 0, "timeout_sec": 10, "http_method": "GE T",
+                          "cert_warn_days": 5, "cert_min_days": 7, "response_critical_ms": 0 },
+            "retention_days": 0,
+            "projects": [{
+                "id": "proj1",
+                "monitors": [{
+                    "site_key": "a",
+                    "url": "not a url",
+                    "timeout_sec": 0,
+                    "http_method": "BAD METHOD",
+                    "headers": { "X Bad": "1", "X-Token": "line\nbreak" },
+                    "assertions": [
+                        { "type": "status", "in": ["2xx"] },
+                        { "type": "json_path", "path": "$.status" }
+                    ],
+                    "failures_before_down": 0
+                }, {
+                    "site_key": "b",
+                    "url": "ftp://example.com",
+                    "response_warn_ms": 5000,
+                    "response_critical_ms": 5000
+                }]
+            }]
+        }"#);
+        assert_eq!(errors, vec![
+            "retention_days: must be greater than 0",
+            "defaults.interval_sec: must be greater than 0",
+            "defaults.http_method: invalid HTTP method 'GE T'",
+            "defaults.cert_min_days: must not exceed cert_warn_days",
+            "defaults.response_critical_ms: must be greater than 0",
+            "projects[0].monitors[0].url: invalid URL: relative URL without a base",
+            "projects[0].monitors[0].timeout_sec: must be greater than 0",
+            "projects[0].monitors[0].http_method: invalid HTTP method 'BAD METHOD'",
+            "projects[0].monitors[0].assertions[1]: 'eq' needs a value",
+            "projects[0].monitors[0].headers.X Bad: invalid header name",
+            "projects[0].monitors[0].headers.X-Token: invalid header value",
+            "projects[0].monitors[0].failures_before_down: must be greater than 0",
+            "projects[0].monitors[1].url: unsupported scheme 'ftp'",
+            "projects[0].monitors[1].response_warn_ms: must be less than response_critical_ms (5000)",
+        ]);
+    }
+}
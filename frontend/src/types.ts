@@ -4,10 +4,10 @@ export interface SiteStatus {
   url: string;
   status_code: number;
   response_ms: number | null;
-  is_up: boolean;
+  is_up: boolean | null;
   error_type: string | null;
   error_message: string | null;
-  last_checked_at: string;
+  last_checked_at: string | null;
   last_up_at: string | null;
 }
 